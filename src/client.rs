@@ -0,0 +1,4265 @@
+use std::{
+    any::Any,
+    borrow::Cow,
+    collections::{HashMap, HashSet, VecDeque},
+    error::Error,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::{
+    future::{BoxFuture, FutureExt, Shared},
+    stream::{self, Stream, StreamExt},
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use tokio::sync::oneshot;
+
+use crate::{
+    summarize_errors, APIData, APIError, ApiResult, Cached, InternalError, KeyData,
+    NicknameHistory, NicknameHistoryRef, NicknameHistoryResult, PlayerData, PrefetchSummary,
+    PunishmentData, PunishmentId, RateLimit, StaffDiff, StaffRank, StaffTracker, StaffTrackerRef,
+    API_VERSION,
+};
+
+/// The public API's base URL for [`API_VERSION`].
+fn default_base_url() -> String {
+    format!("https://hypixel.cactive.network/api/v{API_VERSION}")
+}
+
+/// How many punishments [`Client::punishments_for_player`] requests at a time.
+pub const PUNISHMENT_CONCURRENCY: usize = 4;
+
+/// How many players [`Client::prefetch`] requests at a time.
+pub const PREFETCH_CONCURRENCY: usize = 4;
+
+macro_rules! log_debug {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+        #[cfg(feature = "log")]
+        log::debug!($($arg)*);
+        #[cfg(not(any(feature = "tracing", feature = "log")))]
+        let _ = format_args!($($arg)*);
+    }};
+}
+
+type InFlight = Shared<BoxFuture<'static, Fetched>>;
+type RequestHook = Arc<dyn Fn(&str, Option<u16>, Duration) + Send + Sync>;
+type RequestBuilderHook =
+    Arc<dyn Fn(reqwest::RequestBuilder) -> reqwest::RequestBuilder + Send + Sync>;
+#[cfg(feature = "tracing")]
+type SpanNamer = Arc<dyn Fn(&str, &[(&str, &str)]) -> String + Send + Sync>;
+type ResponseInspector = Arc<dyn Fn(&str, u16, &reqwest::header::HeaderMap, &str) + Send + Sync>;
+
+/// A client for the API, which is cheap to clone into spawned tasks.
+///
+/// Clones share the same key, connection pool, local cache and stubbed responses rather than copying them.
+#[derive(Clone)]
+pub struct Client {
+    key: Arc<Mutex<String>>,
+    cache: bool,
+    base_url: String,
+    transport: Transport,
+    key_data: Arc<Mutex<Option<Arc<KeyData>>>>,
+    enabled_endpoints: Arc<Mutex<Option<HashSet<String>>>>,
+    in_flight: Option<Arc<Mutex<HashMap<String, InFlight>>>>,
+    on_request: Option<RequestHook>,
+    max_response_bytes: Option<usize>,
+    local_cache: Option<Arc<Mutex<HashMap<String, CacheEntry>>>>,
+    local_cache_capacity: usize,
+    request_hook: Option<RequestBuilderHook>,
+    inspector: Option<ResponseInspector>,
+    normalize_nicknames: bool,
+    trust_status_code: bool,
+    endpoint_timeouts: Arc<HashMap<String, Duration>>,
+    timeout: Option<Duration>,
+    deadline: Option<Instant>,
+    key_header: Option<reqwest::header::HeaderName>,
+    #[cfg(feature = "tracing")]
+    span_name: Option<SpanNamer>,
+    cache_ttl: Option<Duration>,
+    endpoint_cache_ttls: Arc<HashMap<String, Duration>>,
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+    limiter: Option<Arc<Limiter>>,
+    priority: Priority,
+}
+
+pub struct ClientBuilder {
+    key: String,
+    cache: bool,
+    base_url: String,
+    http2_prior_knowledge: bool,
+    pool_idle_timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    coalesce_requests: bool,
+    on_request: Option<RequestHook>,
+    max_response_bytes: Option<usize>,
+    local_cache: bool,
+    local_cache_capacity: usize,
+    request_hook: Option<RequestBuilderHook>,
+    inspector: Option<ResponseInspector>,
+    normalize_nicknames: bool,
+    trust_status_code: bool,
+    timeout: Option<Duration>,
+    endpoint_timeouts: HashMap<String, Duration>,
+    key_header: Option<reqwest::header::HeaderName>,
+    #[cfg(feature = "tracing")]
+    span_name: Option<SpanNamer>,
+    cache_ttl: Option<Duration>,
+    endpoint_cache_ttls: HashMap<String, Duration>,
+    circuit_breaker: Option<(u32, Duration, Duration)>,
+    max_concurrent_requests: Option<usize>,
+    #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+    danger_accept_invalid_certs: bool,
+}
+
+impl From<reqwest::Error> for InternalError {
+    fn from(error: reqwest::Error) -> Self {
+        let (r#type, code) = network_error_kind(&error);
+        InternalError::internal(r#type, code, error.to_string())
+    }
+}
+
+impl Client {
+    /// Create a new client, providing a key string and a cache boolean.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cactive_hypixel_api::Client;
+    ///
+    /// let key = "my_api_key".to_owned();
+    /// let client = Client::new(key, false);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HTTP client can't be initialised, such as when the system's TLS backend fails to load. Use
+    /// [`Client::builder`] to handle that as an error instead.
+    pub fn new(key: String, cache: bool) -> Self {
+        Client::builder(key)
+            .cache(cache)
+            .build()
+            .expect("the default HTTP client failed to initialise")
+    }
+
+    /// Create a client from the environment, reading the key from `CACTIVE_HYPIXEL_API_KEY` and the cache
+    /// boolean from `CACTIVE_HYPIXEL_CACHE`, which accepts `true`/`false` or `1`/`0` and defaults to `false`.
+    ///
+    /// The variables are only read here, so clients configured through [`Client::new`] or [`Client::builder`]
+    /// are never affected by them. A missing key fails with a `missing-key` error and an unreadable cache value
+    /// with an `invalid-environment` error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let client = Client::from_env().map_err(Errors)?;
+    /// ```
+    pub fn from_env() -> ApiResult<Self> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> ApiResult<Self> {
+        let error = |r#type: &str, code, message: String| {
+            vec![InternalError::internal(r#type, code, message)]
+        };
+
+        let key = var("CACTIVE_HYPIXEL_API_KEY").ok_or_else(|| {
+            error(
+                "missing-key",
+                401,
+                "CACTIVE_HYPIXEL_API_KEY is not set".to_owned(),
+            )
+        })?;
+        let cache = match var("CACTIVE_HYPIXEL_CACHE").as_deref() {
+            None | Some("false" | "0") => false,
+            Some("true" | "1") => true,
+            Some(value) => {
+                return Err(error(
+                    "invalid-environment",
+                    400,
+                    format!("CACTIVE_HYPIXEL_CACHE is {value:?}, expected true, false, 1 or 0"),
+                ))
+            }
+        };
+        Ok(Self::new(key, cache))
+    }
+
+    /// Create a client and immediately validate its key, failing with a `rejected-key` error if the key is
+    /// invalid, inactive or expired.
+    ///
+    /// The key data fetched for validation is kept for later calls to [`Client::can_use`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let key = "my_api_key".to_owned();
+    /// let client = Client::connect(key, false).await.map_err(Errors)?;
+    /// ```
+    pub async fn connect(key: String, cache: bool) -> ApiResult<Self> {
+        Self::new(key, cache).validated().await
+    }
+
+    async fn validated(self) -> ApiResult<Self> {
+        let key_data = self.own_key_data().await?;
+        let problem = match (key_data.valid, key_data.active, key_data.is_expired()) {
+            (false, _, _) => "invalid",
+            (_, false, _) => "inactive",
+            (_, _, true) => "expired",
+            _ => return Ok(self),
+        };
+        Err(vec![InternalError {
+            endpoint: "key".to_owned(),
+            ..InternalError::internal(
+                "rejected-key",
+                403,
+                format!("the client's key is {problem}"),
+            )
+        }])
+    }
+
+    /// Create an offline client which resolves every call from canned responses instead of HTTP.
+    ///
+    /// Stubs are keyed by endpoint name (`nickname-history`, `player-data`, `staff-tracker`,
+    /// `punishment-data` or `key`) and hold the raw JSON body the API would return. This is
+    /// intended for testing code which depends on the client only, and should never be used in production.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use cactive_hypixel_api::Client;
+    ///
+    /// let stubs = HashMap::from([(
+    ///     "staff-tracker".to_owned(),
+    ///     r#"{"success":true,"id":"stub","data":[]}"#.to_owned(),
+    /// )]);
+    /// let client = Client::with_stub(stubs);
+    /// ```
+    pub fn with_stub(stubs: HashMap<String, String>) -> Self {
+        Client::builder("stub".to_owned()).build_with(Transport::Stub(Arc::new(stubs)))
+    }
+
+    /// Create a builder for a client, providing a key string, for tuning the underlying connection.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cactive_hypixel_api::Client;
+    ///
+    /// let client = Client::builder("my_api_key".to_owned())
+    ///     .cache(true)
+    ///     .http2_prior_knowledge(true)
+    ///     .build()?;
+    /// ```
+    pub fn builder(key: String) -> ClientBuilder {
+        ClientBuilder {
+            key,
+            cache: false,
+            base_url: default_base_url(),
+            http2_prior_knowledge: false,
+            pool_idle_timeout: None,
+            connect_timeout: None,
+            coalesce_requests: false,
+            on_request: None,
+            max_response_bytes: None,
+            local_cache: false,
+            local_cache_capacity: DEFAULT_LOCAL_CACHE_CAPACITY,
+            request_hook: None,
+            inspector: None,
+            normalize_nicknames: false,
+            trust_status_code: false,
+            timeout: None,
+            endpoint_timeouts: HashMap::new(),
+            key_header: None,
+            #[cfg(feature = "tracing")]
+            span_name: None,
+            cache_ttl: None,
+            endpoint_cache_ttls: HashMap::new(),
+            circuit_breaker: None,
+            max_concurrent_requests: None,
+            #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+            danger_accept_invalid_certs: false,
+        }
+    }
+
+    /// Retrieve an ascending vector of players referenced from the nickname parameter.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let nickname = "angry_and_free".to_owned();
+    /// let data = match client.nickname_history(nickname).await {
+    ///     Ok(data) => data,
+    ///     Err(err) => return println!("{}", err[0].message),
+    /// };
+    /// ```
+    pub async fn nickname_history(&self, nickname: String) -> ApiResult<Vec<NicknameHistory>> {
+        self.nickname_history_with(nickname, &[]).await
+    }
+
+    /// Like [`Client::nickname_history`], splitting the entries into those currently active and the historical
+    /// ones by their `active` flag.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let nickname = "angry_and_free".to_owned();
+    /// let current = client.nickname_history_split(nickname).await?.active;
+    /// ```
+    pub async fn nickname_history_split(
+        &self,
+        nickname: String,
+    ) -> ApiResult<NicknameHistoryResult> {
+        self.nickname_history(nickname).await.map(Into::into)
+    }
+
+    /// Like [`Client::nickname_history`], appending extra query parameters after the standard ones.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let nickname = "angry_and_free".to_owned();
+    /// let data = client.nickname_history_with(nickname, &[("include_ips", "true")]).await;
+    /// ```
+    pub async fn nickname_history_with(
+        &self,
+        nickname: String,
+        params: &[(&str, &str)],
+    ) -> ApiResult<Vec<NicknameHistory>> {
+        let nickname = self.normalize_nickname(&nickname);
+        self.request_data("nickname-history", &[("nickname", &nickname)], params)
+            .await
+    }
+
+    /// Retrieve a structure of player data, providing a uuid parameter.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let uuid = "eea2d4fd-a8b8-413b-9439-f06faaf7e109".to_owned();
+    /// let data = match client.player_data(uuid).await {
+    ///     Ok(data) => data,
+    ///     Err(err) => return println!("{}", err[0].message),
+    /// };
+    /// ```
+    pub async fn player_data(&self, uuid: String) -> ApiResult<PlayerData> {
+        self.player_data_with(uuid, &[]).await
+    }
+
+    /// Retrieve the nickname a player is currently using, or `None` if they aren't using one, see
+    /// [`PlayerData::current_nickname`].
+    ///
+    /// The API has no lighter endpoint for this, so the full player data is requested and only the nickname kept.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let uuid = "eea2d4fd-a8b8-413b-9439-f06faaf7e109".to_owned();
+    /// match client.current_nickname(uuid).await? {
+    ///     Some(nickname) => println!("nicked as {nickname}"),
+    ///     None => println!("not nicked"),
+    /// }
+    /// ```
+    pub async fn current_nickname(&self, uuid: String) -> ApiResult<Option<String>> {
+        let player = self.player_data(uuid).await?;
+        Ok(player.current_nickname().map(str::to_owned))
+    }
+
+    /// Like [`Client::player_data`], appending extra query parameters after the standard ones.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let uuid = "eea2d4fd-a8b8-413b-9439-f06faaf7e109".to_owned();
+    /// let data = client.player_data_with(uuid, &[("include_ips", "true")]).await;
+    /// ```
+    pub async fn player_data_with(
+        &self,
+        uuid: String,
+        params: &[(&str, &str)],
+    ) -> ApiResult<PlayerData> {
+        self.request_data("player-data", &[("uuid", &uuid)], params)
+            .await
+    }
+
+    /// Retrieve an ascending vector of Hypixel staff providing a filter ("all", "online", "offline") parameter.
+    ///
+    /// The endpoint returns every matching staff member in one response and only reports their current status:
+    /// it has no paging or historical queries, so there is nothing further to collect.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let filter = "online".to_owned();
+    /// let data = match client.staff_tracker(filter).await {
+    ///     Ok(data) => data,
+    ///     Err(err) => return println!("{}", err[0].message),
+    /// };
+    /// ```
+    pub async fn staff_tracker(&self, filter: String) -> ApiResult<Vec<StaffTracker>> {
+        self.staff_tracker_with(filter, &[]).await
+    }
+
+    /// Like [`Client::staff_tracker`], sorting the staff on the client rather than relying on the order the API
+    /// returns, so the output is deterministic. Staff are sorted by the seniority of their [`StaffRank`], from
+    /// owners to helpers followed by any other rank, then by the `rank` as returned and their UUID.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let filter = "online".to_owned();
+    /// let data = client.staff_tracker_sorted(filter).await;
+    /// ```
+    pub async fn staff_tracker_sorted(&self, filter: String) -> ApiResult<Vec<StaffTracker>> {
+        fn seniority(member: &StaffTracker) -> u8 {
+            match member.staff_rank() {
+                StaffRank::Owner => 0,
+                StaffRank::Admin => 1,
+                StaffRank::GameMaster => 2,
+                StaffRank::Moderator => 3,
+                StaffRank::Helper => 4,
+                StaffRank::Other(_) => 5,
+            }
+        }
+
+        let mut staff = self.staff_tracker(filter).await?;
+        staff.sort_by(|a, b| {
+            seniority(a)
+                .cmp(&seniority(b))
+                .then_with(|| a.rank.cmp(&b.rank))
+                .then_with(|| a.uuid.cmp(&b.uuid))
+        });
+        Ok(staff)
+    }
+
+    /// Like [`Client::staff_tracker`], appending extra query parameters after the standard ones.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let filter = "online".to_owned();
+    /// let data = client.staff_tracker_with(filter, &[("include_ips", "true")]).await;
+    /// ```
+    pub async fn staff_tracker_with(
+        &self,
+        filter: String,
+        params: &[(&str, &str)],
+    ) -> ApiResult<Vec<StaffTracker>> {
+        self.request_data("staff-tracker", &[("filter", &filter)], params)
+            .await
+    }
+
+    /// Retrieve a structure of player data, providing a nickname parameter which is resolved to a uuid first.
+    ///
+    /// The nickname must be active for exactly one player: a `nickname-not-found` error is returned if no
+    /// entry is active, and an `ambiguous-nickname` error if active entries belong to more than one player.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let nickname = "angry_and_free".to_owned();
+    /// let data = match client.player_data_by_nickname(nickname).await {
+    ///     Ok(data) => data,
+    ///     Err(err) => return println!("{}", err[0].message),
+    /// };
+    /// ```
+    pub async fn player_data_by_nickname(&self, nickname: String) -> ApiResult<PlayerData> {
+        let history = self.nickname_history(nickname.clone()).await?;
+        let mut uuids = history
+            .iter()
+            .filter(|entry| entry.active)
+            .map(|entry| entry.uuid.as_str())
+            .collect::<Vec<_>>();
+        uuids.sort_unstable();
+        uuids.dedup();
+
+        let error = |r#type: &str, code, message: String| {
+            Err(vec![InternalError {
+                endpoint: "nickname-history".to_owned(),
+                ..InternalError::internal(r#type, code, message)
+            }])
+        };
+        match uuids[..] {
+            [uuid] => self.player_data(uuid.to_owned()).await,
+            [] => error(
+                "nickname-not-found",
+                404,
+                format!("no player is currently using the nickname {nickname}"),
+            ),
+            _ => error(
+                "ambiguous-nickname",
+                409,
+                format!(
+                    "the nickname {nickname} is active for {} players",
+                    uuids.len()
+                ),
+            ),
+        }
+    }
+
+    /// Like [`Client::nickname_history`], but borrowing strings from a caller-owned buffer to avoid allocating them.
+    ///
+    /// The response body replaces the contents of `buffer`, and the returned entries borrow from it, so the
+    /// buffer can't be modified or dropped while they're alive. Strings containing JSON escapes are still allocated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let mut buffer = Vec::new();
+    /// let nickname = "angry_and_free".to_owned();
+    /// let data = match client.nickname_history_borrowed(nickname, &mut buffer).await {
+    ///     Ok(data) => data,
+    ///     Err(err) => return println!("{}", err[0].message),
+    /// };
+    /// ```
+    pub async fn nickname_history_borrowed<'a>(
+        &self,
+        nickname: String,
+        buffer: &'a mut Vec<u8>,
+    ) -> ApiResult<Vec<NicknameHistoryRef<'a>>> {
+        let nickname = self.normalize_nickname(&nickname);
+        let body = self
+            .data_body("nickname-history", &[("nickname", &nickname)], &[])
+            .await
+            .map_err(|errors| with_endpoint(errors, "nickname-history"))?;
+        buffer.clear();
+        buffer.extend_from_slice(&body.data);
+        parse_body(buffer).map_err(|errors| with_endpoint(errors, "nickname-history"))
+    }
+
+    /// Like [`Client::staff_tracker`], but borrowing strings from a caller-owned buffer to avoid allocating them.
+    ///
+    /// The same lifetime constraints as [`Client::nickname_history_borrowed`] apply.
+    pub async fn staff_tracker_borrowed<'a>(
+        &self,
+        filter: String,
+        buffer: &'a mut Vec<u8>,
+    ) -> ApiResult<Vec<StaffTrackerRef<'a>>> {
+        let body = self
+            .data_body("staff-tracker", &[("filter", &filter)], &[])
+            .await
+            .map_err(|errors| with_endpoint(errors, "staff-tracker"))?;
+        buffer.clear();
+        buffer.extend_from_slice(&body.data);
+        parse_body(buffer).map_err(|errors| with_endpoint(errors, "staff-tracker"))
+    }
+
+    /// Lazily resolve nicknames to player data, with at most `concurrency` lookups in flight at once.
+    ///
+    /// Each nickname is resolved as in [`Client::player_data_by_nickname`], so a nickname which is unused or
+    /// ambiguous yields an error item instead of ending the stream. Results are yielded in the order of the nicknames.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use futures::StreamExt;
+    ///
+    /// let nicknames = vec!["angry_and_free".to_owned(), "caykey".to_owned()];
+    /// let mut players = client.player_data_by_nicknames(nicknames, 4);
+    /// while let Some(player) = players.next().await {
+    ///     match player {
+    ///         Ok(data) => println!("{}", data.uuid),
+    ///         Err(err) => println!("{}", err[0].message),
+    ///     }
+    /// }
+    /// ```
+    pub fn player_data_by_nicknames<'a, I>(
+        &'a self,
+        nicknames: I,
+        concurrency: usize,
+    ) -> impl Stream<Item = ApiResult<PlayerData>> + 'a
+    where
+        I: IntoIterator<Item = String>,
+        I::IntoIter: 'a,
+    {
+        stream::iter(nicknames)
+            .map(move |nickname| self.player_data_by_nickname(nickname))
+            .buffered(concurrency.max(1))
+    }
+
+    /// Retrieve the player data of many UUIDs, running up to `concurrency` requests at a time.
+    ///
+    /// Each UUID is paired with its own result, in the order the UUIDs were given, so one failing lookup doesn't
+    /// discard the others. Pass the results to [`split_results`](crate::split_results) to handle the successes and failures separately.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cactive_hypixel_api::split_results;
+    ///
+    /// let uuids = vec!["7d9c4e7bc5d24bb2b4c1c6a1e4bb2e60".to_owned()];
+    /// let (players, failures) = split_results(client.player_data_many(uuids, 4).await);
+    /// for (uuid, err) in failures {
+    ///     println!("{uuid}: {}", err[0].message);
+    /// }
+    /// ```
+    pub async fn player_data_many<I>(
+        &self,
+        uuids: I,
+        concurrency: usize,
+    ) -> Vec<(String, ApiResult<PlayerData>)>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        stream::iter(uuids)
+            .map(|uuid| async move {
+                let result = self.player_data(uuid.clone()).await;
+                (uuid, result)
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Like [`Client::player_data_many`], but failing fast for callers that need every player, returning the
+    /// players in the order the UUIDs were given.
+    ///
+    /// The first lookup to fail is returned with its UUID as soon as it fails, and the outstanding requests are
+    /// dropped: their HTTP calls are aborted and their connections closed, as with any cancelled request, and
+    /// UUIDs not yet started are never requested. Use [`Client::player_data_many`] to collect every result
+    /// instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// match client.player_data_all(uuids, 4).await {
+    ///     Ok(players) => println!("loaded {} players", players.len()),
+    ///     Err((uuid, err)) => println!("{uuid}: {}", err[0].message),
+    /// }
+    /// ```
+    pub async fn player_data_all<I>(
+        &self,
+        uuids: I,
+        concurrency: usize,
+    ) -> Result<Vec<PlayerData>, (String, Vec<InternalError>)>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut results = stream::iter(uuids.into_iter().enumerate())
+            .map(|(index, uuid)| async move {
+                let result = self.player_data(uuid.clone()).await;
+                (index, uuid, result)
+            })
+            .buffer_unordered(concurrency.max(1));
+        let mut players = Vec::new();
+        while let Some((index, uuid, result)) = results.next().await {
+            match result {
+                Ok(player) => players.push((index, player)),
+                Err(errors) => return Err((uuid, errors)),
+            }
+        }
+        players.sort_by_key(|(index, _)| *index);
+        Ok(players.into_iter().map(|(_, player)| player).collect())
+    }
+
+    /// A batch of player lookups whose failure handling is chosen by `fail_fast`.
+    ///
+    /// With `fail_fast` disabled this always succeeds with each UUID's result, exactly like
+    /// [`Client::player_data_many`]. With it enabled this behaves like [`Client::player_data_all`]: the first
+    /// lookup to fail is returned with its UUID as soon as it fails, and the outstanding requests are dropped, so
+    /// their HTTP calls are aborted and their connections closed, and UUIDs not yet started are never requested.
+    /// Otherwise every result is `Ok`, in the order the UUIDs were given. Call those methods directly when the
+    /// mode is fixed, to avoid the unused half of this return type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// match client.player_data_batch(uuids, 4, hard_dependency).await {
+    ///     Ok(results) => println!("looked up {} players", results.len()),
+    ///     Err((uuid, err)) => println!("{uuid}: {}", err[0].message),
+    /// }
+    /// ```
+    pub async fn player_data_batch<I>(
+        &self,
+        uuids: I,
+        concurrency: usize,
+        fail_fast: bool,
+    ) -> Result<Vec<(String, ApiResult<PlayerData>)>, (String, Vec<InternalError>)>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        if !fail_fast {
+            return Ok(self.player_data_many(uuids, concurrency).await);
+        }
+
+        let players = self.player_data_all(uuids, concurrency).await?;
+        Ok(players
+            .into_iter()
+            .map(|player| (player.uuid.clone(), Ok(player)))
+            .collect())
+    }
+
+    /// Load the player data of many UUIDs into the local cache, requesting up to [`PREFETCH_CONCURRENCY`] at a
+    /// time, and report how many succeeded.
+    ///
+    /// This only helps with [`ClientBuilder::local_cache`] enabled, and only for responses with an `ETag` unless a
+    /// [`ClientBuilder::cache_ttl`] is set. Later [`Client::player_data`] calls for these UUIDs are answered
+    /// without a request while their entry is within its time to live. After that, or with no time to live, they
+    /// are revalidated with the `ETag` and answered from the cache while the data hasn't changed. Entries can
+    /// still be evicted once the cache reaches [`ClientBuilder::local_cache_capacity`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let summary = client.prefetch(uuids).await;
+    /// println!("{} of {} players cached", summary.succeeded, summary.succeeded + summary.failed);
+    /// ```
+    pub async fn prefetch(&self, uuids: Vec<String>) -> PrefetchSummary {
+        let results = self.player_data_many(uuids, PREFETCH_CONCURRENCY).await;
+        let succeeded = results.iter().filter(|(_, result)| result.is_ok()).count();
+        PrefetchSummary {
+            succeeded,
+            failed: results.len() - succeeded,
+        }
+    }
+
+    /// Retrieve a player's data, then the full punishment data of each of their infractions.
+    ///
+    /// At most [`PUNISHMENT_CONCURRENCY`] punishments are requested at a time, and the results are in the same
+    /// order as [`PlayerData::infractions`]. A failed punishment lookup doesn't affect the others, but failing to
+    /// retrieve the player data fails the whole call.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let uuid = "7d9c4e7bc5d24bb2b4c1c6a1e4bb2e60".to_owned();
+    /// for punishment in client.punishments_for_player(uuid).await.map_err(Errors)? {
+    ///     match punishment {
+    ///         Ok(data) => println!("{}: {}", data.id, data.reason),
+    ///         Err(err) => println!("{}", err[0].message),
+    ///     }
+    /// }
+    /// ```
+    pub async fn punishments_for_player(
+        &self,
+        uuid: String,
+    ) -> ApiResult<Vec<ApiResult<PunishmentData>>> {
+        let player = self.player_data(uuid).await?;
+        Ok(stream::iter(player.punishment_ids())
+            .map(|id| self.punishment_data(id.to_owned()))
+            .buffered(PUNISHMENT_CONCURRENCY)
+            .collect()
+            .await)
+    }
+
+    /// Poll the staff tracker every `interval`, yielding which staff went online or offline since the previous poll.
+    ///
+    /// The first item compares against nobody being online, so it reports every online staff member. A failed
+    /// poll yields an error item and the stream carries on, comparing the next successful poll against the last
+    /// successful one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use futures::StreamExt;
+    ///
+    /// let mut diffs = client.watch_staff(Duration::from_secs(60));
+    /// while let Some(diff) = diffs.next().await {
+    ///     match diff {
+    ///         Ok(diff) => println!("{} staff came online", diff.went_online.len()),
+    ///         Err(err) => println!("{}", err[0].message),
+    ///     }
+    /// }
+    /// ```
+    pub fn watch_staff(&self, interval: Duration) -> impl Stream<Item = ApiResult<StaffDiff>> {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        stream::unfold(
+            (self.clone(), ticker, HashSet::new()),
+            |(client, mut ticker, previous)| async move {
+                ticker.tick().await;
+                let staff = match client.staff_tracker("all".to_owned()).await {
+                    Ok(staff) => staff,
+                    Err(errors) => return Some((Err(errors), (client, ticker, previous))),
+                };
+
+                let online = staff
+                    .into_iter()
+                    .filter(|member| member.online == Some(true))
+                    .map(|member| member.uuid)
+                    .collect::<HashSet<_>>();
+                let diff = StaffDiff {
+                    went_online: online.difference(&previous).cloned().collect(),
+                    went_offline: previous.difference(&online).cloned().collect(),
+                };
+                Some((Ok(diff), (client, ticker, online)))
+            },
+        )
+    }
+
+    /// Retrieve a structure of punishment data, providing an ID parameter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let id = "C256D602".to_owned();
+    /// let data = match client.punishment_data(id).await {
+    ///     Ok(data) => data,
+    ///     Err(err) => return println!("{}", err[0].message),
+    /// };
+    /// ```
+    pub async fn punishment_data(&self, id: String) -> ApiResult<PunishmentData> {
+        self.punishment_data_with(id, &[]).await
+    }
+
+    /// Like [`Client::punishment_data`], appending extra query parameters after the standard ones.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let id = "C256D602".to_owned();
+    /// let data = client.punishment_data_with(id, &[("include_ips", "true")]).await;
+    /// ```
+    pub async fn punishment_data_with(
+        &self,
+        id: String,
+        params: &[(&str, &str)],
+    ) -> ApiResult<PunishmentData> {
+        let id = punishment_id(&id)?;
+        self.request_data("punishment-data", &[("id", id.as_str())], params)
+            .await
+    }
+
+    /// Like [`Client::nickname_history`], also reporting whether the response was served from the local cache and the
+    /// rate limit it reported.
+    pub async fn nickname_history_cached(
+        &self,
+        nickname: String,
+    ) -> ApiResult<Cached<Vec<NicknameHistory>>> {
+        let nickname = self.normalize_nickname(&nickname);
+        self.request_data_cached("nickname-history", &[("nickname", &nickname)], &[])
+            .await
+    }
+
+    /// Like [`Client::nickname_history`], also returning the response headers, see [`Cached::headers`] for those
+    /// commonly present.
+    pub async fn nickname_history_with_headers(
+        &self,
+        nickname: String,
+    ) -> ApiResult<(Vec<NicknameHistory>, reqwest::header::HeaderMap)> {
+        let cached = self.nickname_history_cached(nickname).await?;
+        Ok((cached.data, cached.headers))
+    }
+
+    /// Like [`Client::player_data`], also reporting whether the response was served from the local cache and the
+    /// rate limit it reported.
+    pub async fn player_data_cached(&self, uuid: String) -> ApiResult<Cached<PlayerData>> {
+        self.request_data_cached("player-data", &[("uuid", &uuid)], &[])
+            .await
+    }
+
+    /// Like [`Client::player_data`], also returning the response headers, see [`Cached::headers`] for those
+    /// commonly present.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let (data, headers) = client.player_data_with_headers(uuid).await.map_err(Errors)?;
+    /// if let Some(etag) = headers.get("etag") {
+    ///     println!("{} has version {:?}", data.uuid, etag);
+    /// }
+    /// ```
+    pub async fn player_data_with_headers(
+        &self,
+        uuid: String,
+    ) -> ApiResult<(PlayerData, reqwest::header::HeaderMap)> {
+        let cached = self.player_data_cached(uuid).await?;
+        Ok((cached.data, cached.headers))
+    }
+
+    /// Like [`Client::player_data_cached`], returning the player in an [`Arc`] shared by every caller served the
+    /// same response.
+    ///
+    /// With [`ClientBuilder::local_cache`] enabled, the decoded player is kept alongside the cached response, so
+    /// each request answered from the cache hands out another reference to one allocation rather than decoding
+    /// and cloning it again, which suits many readers of the same large player. The shared value is replaced once
+    /// the API returns a changed response. Without the local cache every call decodes its own copy.
+    ///
+    /// The data is shared, so it can't be mutated in place: clone it out of the `Arc` first, such as with
+    /// [`Arc::unwrap_or_clone`].
+    pub async fn player_data_shared(&self, uuid: String) -> ApiResult<Cached<Arc<PlayerData>>> {
+        self.request_data_shared("player-data", &[("uuid", &uuid)])
+            .await
+    }
+
+    /// Like [`Client::staff_tracker`], also reporting whether the response was served from the local cache and the
+    /// rate limit it reported.
+    pub async fn staff_tracker_cached(
+        &self,
+        filter: String,
+    ) -> ApiResult<Cached<Vec<StaffTracker>>> {
+        self.request_data_cached("staff-tracker", &[("filter", &filter)], &[])
+            .await
+    }
+
+    /// Like [`Client::staff_tracker`], also returning the response headers, see [`Cached::headers`] for those
+    /// commonly present.
+    pub async fn staff_tracker_with_headers(
+        &self,
+        filter: String,
+    ) -> ApiResult<(Vec<StaffTracker>, reqwest::header::HeaderMap)> {
+        let cached = self.staff_tracker_cached(filter).await?;
+        Ok((cached.data, cached.headers))
+    }
+
+    /// Like [`Client::punishment_data`], also reporting whether the response was served from the local cache and the
+    /// rate limit it reported.
+    pub async fn punishment_data_cached(&self, id: String) -> ApiResult<Cached<PunishmentData>> {
+        let id = punishment_id(&id)?;
+        self.request_data_cached("punishment-data", &[("id", id.as_str())], &[])
+            .await
+    }
+
+    /// Like [`Client::punishment_data`], also returning the response headers, see [`Cached::headers`] for those
+    /// commonly present.
+    pub async fn punishment_data_with_headers(
+        &self,
+        id: String,
+    ) -> ApiResult<(PunishmentData, reqwest::header::HeaderMap)> {
+        let cached = self.punishment_data_cached(id).await?;
+        Ok((cached.data, cached.headers))
+    }
+
+    /// Retrieve the key data of the provided an key parameter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let key = "my_api_key".to_owned();
+    /// let data = match client.key_data(key).await {
+    ///     Ok(data) => data,
+    ///     Err(err) => return println!("{}", err[0].message),
+    /// };
+    /// ```
+    pub async fn key_data(&self, key: String) -> ApiResult<KeyData> {
+        self.request("key", &[("key", &key)]).await
+    }
+
+    /// Retrieve the untyped data of any endpoint, providing the endpoint name and its query parameters.
+    ///
+    /// The key and cache parameters and the success/error handling are applied as usual, which allows
+    /// reading fields this crate doesn't model yet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let uuid = "eea2d4fd-a8b8-413b-9439-f06faaf7e109";
+    /// let data = match client.get_value("player-data", &[("uuid", uuid)]).await {
+    ///     Ok(data) => data,
+    ///     Err(err) => return println!("{}", err[0].message),
+    /// };
+    /// println!("{}", data["tracker"]["server"]);
+    /// ```
+    pub async fn get_value(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+    ) -> ApiResult<serde_json::Value> {
+        self.request_data(endpoint, params, &[]).await
+    }
+
+    /// Check whether the client's own key is permitted to use an endpoint, such as `"player-data"`.
+    ///
+    /// The key data is fetched once and reused for later checks, and endpoints missing from it are treated as disabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// if !client.can_use("player-data").await? {
+    ///     return println!("This key cannot request player data");
+    /// }
+    /// ```
+    pub async fn can_use(&self, endpoint_id: &str) -> ApiResult<bool> {
+        Ok(self
+            .own_key_data()
+            .await?
+            .endpoints
+            .iter()
+            .any(|endpoint| endpoint.id == endpoint_id && endpoint.status))
+    }
+
+    /// Discover which endpoints the client's key is permitted to use, so that later calls to any other endpoint
+    /// fail immediately with a `disabled-endpoint` error instead of being sent. This costs one request to the
+    /// `key` endpoint, whose data is kept for [`Client::can_use`] too, and applies to every clone of the client.
+    ///
+    /// Without calling this, every call is sent to the API as usual. The `key` endpoint itself is never
+    /// blocked, and [`Client::set_key`] discards the discovered endpoints, so call this again after changing key.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let client = Client::new(key, false);
+    /// client.init().await.map_err(Errors)?;
+    /// ```
+    pub async fn init(&self) -> ApiResult<()> {
+        let key = self.key();
+        let key_data = self.own_key_data().await?;
+        let enabled = key_data
+            .endpoints
+            .iter()
+            .filter(|endpoint| endpoint.status)
+            .map(|endpoint| endpoint.id.clone())
+            .collect();
+        let current = self.key.lock().unwrap();
+        if *current == key {
+            *self.enabled_endpoints.lock().unwrap() = Some(enabled);
+        }
+        Ok(())
+    }
+
+    async fn own_key_data(&self) -> ApiResult<Arc<KeyData>> {
+        if let Some(key_data) = self.key_data.lock().unwrap().clone() {
+            return Ok(key_data);
+        }
+
+        let key = self.key();
+        let key_data = Arc::new(self.key_data(key.clone()).await?);
+        for endpoint in key_data.mismatched_endpoints() {
+            log_warn!(
+                "the key uses version {} of the {} endpoint, but this crate targets version {API_VERSION}",
+                endpoint.version,
+                endpoint.id
+            );
+        }
+        let current = self.key.lock().unwrap();
+        if *current == key {
+            *self.key_data.lock().unwrap() = Some(key_data.clone());
+        }
+        Ok(key_data)
+    }
+
+    /// Replace the key used by this client and every clone of it, keeping the connection pool.
+    ///
+    /// The swap is atomic and safe to call from any thread while requests are running: requests already sent
+    /// keep the old key, and every request started afterwards uses the new one. Key data cached for
+    /// [`Client::can_use`], the endpoints discovered by [`Client::init`] and the responses in the local cache are
+    /// discarded, and requests started afterwards don't share a response with one still in flight, so nothing
+    /// fetched with the old key is served for the new one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// client.set_key("my_new_api_key".to_owned());
+    /// ```
+    pub fn set_key(&self, key: String) {
+        let mut current = self.key.lock().unwrap();
+        *current = key;
+        *self.key_data.lock().unwrap() = None;
+        *self.enabled_endpoints.lock().unwrap() = None;
+        if let Some(local_cache) = &self.local_cache {
+            local_cache.lock().unwrap().clear();
+        }
+        if let Some(in_flight) = &self.in_flight {
+            in_flight.lock().unwrap().clear();
+        }
+    }
+
+    /// Remove every entry referencing a player from the local cache shared by this client's clones, such as when
+    /// their data is known to have changed, and return how many were removed.
+    ///
+    /// Entries are keyed by endpoint and query parameters, excluding the key, so one is removed if its `uuid`
+    /// parameter is the player's, as for [`Client::player_data`], or if any `uuid` field in its data is, as for
+    /// a [`Client::nickname_history`] or [`Client::staff_tracker`] listing the player. Other fields holding
+    /// UUIDs, such as a punishment's `executor`, aren't considered. UUIDs match with or without dashes and in
+    /// any case. This is a no-op without [`ClientBuilder::local_cache`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// client.invalidate_player("eea2d4fd-a8b8-413b-9439-f06faaf7e109");
+    /// ```
+    pub fn invalidate_player(&self, uuid: &str) -> usize {
+        let local_cache = match &self.local_cache {
+            Some(local_cache) => local_cache,
+            None => return 0,
+        };
+        let uuid = compact_uuid(uuid);
+        let mut matching = Vec::new();
+        let mut bodies = Vec::new();
+        for (request_key, entry) in local_cache.lock().unwrap().iter() {
+            match entry
+                .query
+                .iter()
+                .any(|(name, value)| name == "uuid" && compact_uuid(value) == uuid)
+            {
+                true => matching.push(request_key.clone()),
+                false => bodies.push((request_key.clone(), entry.body.clone())),
+            }
+        }
+        matching.extend(
+            bodies
+                .into_iter()
+                .filter(|(_, body)| references_player(body, &uuid))
+                .map(|(request_key, _)| request_key),
+        );
+
+        let mut local_cache = local_cache.lock().unwrap();
+        matching
+            .iter()
+            .filter(|request_key| local_cache.remove(*request_key).is_some())
+            .count()
+    }
+
+    /// A clone of this client whose requests must all finish by the given instant, for a batch of calls sharing
+    /// one overall latency budget.
+    ///
+    /// Each request is given the time remaining until the deadline as its timeout, or the client's own timeout
+    /// for the endpoint if that is shorter, and fails with a `timeout` error when it runs out. A request started
+    /// after the deadline has passed fails immediately with a `deadline-exceeded` error without being sent, as
+    /// does one still waiting for a slot of [`ClientBuilder::max_concurrent_requests`] when the deadline passes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let client = client.with_deadline(Instant::now() + Duration::from_secs(2));
+    /// let (player, staff) = tokio::join!(
+    ///     client.player_data(uuid),
+    ///     client.staff_tracker("online".to_owned()),
+    /// );
+    /// ```
+    pub fn with_deadline(&self, deadline: Instant) -> Client {
+        Client {
+            deadline: Some(deadline),
+            ..self.clone()
+        }
+    }
+
+    /// A clone of this client whose requests wait for a slot of [`ClientBuilder::max_concurrent_requests`] with
+    /// the given priority, such as to keep interactive lookups ahead of a background batch. Requests have
+    /// [`Priority::Normal`] by default, and the priority has no effect without a concurrency limit.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let background = client.with_priority(Priority::Low);
+    /// let summary = background.prefetch(uuids).await;
+    /// ```
+    pub fn with_priority(&self, priority: Priority) -> Client {
+        Client {
+            priority,
+            ..self.clone()
+        }
+    }
+
+    fn normalize_nickname<'a>(&self, nickname: &'a str) -> Cow<'a, str> {
+        match self.normalize_nicknames {
+            true => Cow::Owned(nickname.trim().to_lowercase()),
+            false => Cow::Borrowed(nickname),
+        }
+    }
+
+    fn key(&self) -> String {
+        self.key.lock().unwrap().clone()
+    }
+
+    /// Build the URL a request to an endpoint would be sent to, with the key redacted, for debugging.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let url = client.build_url("player-data", &[("uuid", "eea2d4fd-a8b8-413b-9439-f06faaf7e109")]);
+    /// assert!(url.contains("key=REDACTED"));
+    /// ```
+    pub fn build_url(&self, endpoint: &str, params: &[(&str, &str)]) -> String {
+        let cache = self.cache.to_string();
+        let mut query = standard_query("", &cache, params);
+        if self.key_header.is_some() {
+            query.retain(|(name, _)| *name != "key");
+        }
+        self.redacted_url(endpoint, &query)
+    }
+
+    /// The version of the API this client targets, [`API_VERSION`], whatever its base URL.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// println!("requesting v{} of the API", client.api_version());
+    /// ```
+    pub fn api_version(&self) -> u8 {
+        API_VERSION
+    }
+
+    fn endpoint_url(&self, endpoint: &str) -> String {
+        format!("{}/{}", self.base_url, endpoint.trim_start_matches('/'))
+    }
+
+    /// The URL a request was sent to, with the key redacted as in [`Client::build_url`].
+    fn redacted_url(&self, endpoint: &str, query: &[(&str, &str)]) -> String {
+        let query = query.iter().map(|&(name, value)| match name {
+            "key" => (name, "REDACTED"),
+            _ => (name, value),
+        });
+        let url = self.endpoint_url(endpoint);
+        reqwest::Url::parse_with_params(&url, query).map_or(url, String::from)
+    }
+
+    async fn request_data<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+        extra: &[(&str, &str)],
+    ) -> ApiResult<T> {
+        Ok(self
+            .request_data_cached(endpoint, params, extra)
+            .await?
+            .data)
+    }
+
+    async fn request_data_cached<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+        extra: &[(&str, &str)],
+    ) -> ApiResult<Cached<T>> {
+        self.data_body(endpoint, params, extra)
+            .await
+            .and_then(|body| {
+                let (data, warnings) = parse_body_with_warnings(&body.data)?;
+                Ok(Cached {
+                    data,
+                    from_cache: body.from_cache,
+                    rate_limit: body.rate_limit,
+                    warnings,
+                    headers: body.headers,
+                })
+            })
+            .map_err(|errors| with_endpoint(errors, endpoint))
+    }
+
+    async fn request_data_shared<T: DeserializeOwned + Send + Sync + 'static>(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+    ) -> ApiResult<Cached<Arc<T>>> {
+        let body = self
+            .data_body(endpoint, params, &[])
+            .await
+            .map_err(|errors| with_endpoint(errors, endpoint))?;
+        let local_cache = match &self.local_cache {
+            Some(local_cache) => local_cache,
+            None => {
+                let (data, warnings) = parse_body_with_warnings(&body.data)
+                    .map_err(|errors| with_endpoint(errors, endpoint))?;
+                return Ok(Cached {
+                    data: Arc::new(data),
+                    from_cache: body.from_cache,
+                    rate_limit: body.rate_limit,
+                    warnings,
+                    headers: body.headers,
+                });
+            }
+        };
+
+        let cache = self.cache.to_string();
+        let request_key = request_key(endpoint, &standard_query("", &cache, params));
+        let same_body = |entry: &CacheEntry| entry.body.as_ptr() == body.data.as_ptr();
+        let shared = local_cache
+            .lock()
+            .unwrap()
+            .get(&request_key)
+            .filter(|entry| same_body(entry))
+            .and_then(|entry| entry.shared.clone())
+            .and_then(|(shared, warnings)| Some((shared.downcast::<T>().ok()?, warnings)));
+        let (data, warnings) = match shared {
+            Some(shared) => shared,
+            None => {
+                let (data, warnings) = parse_body_with_warnings(&body.data)
+                    .map_err(|errors| with_endpoint(errors, endpoint))?;
+                let data: Arc<T> = Arc::new(data);
+                if let Some(entry) = local_cache
+                    .lock()
+                    .unwrap()
+                    .get_mut(&request_key)
+                    .filter(|entry| same_body(entry))
+                {
+                    entry.shared = Some((data.clone(), warnings.clone()));
+                }
+                (data, warnings)
+            }
+        };
+        Ok(Cached {
+            data,
+            from_cache: body.from_cache,
+            rate_limit: body.rate_limit,
+            warnings,
+            headers: body.headers,
+        })
+    }
+
+    async fn request<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        query: &[(&str, &str)],
+    ) -> ApiResult<T> {
+        self.request_body(endpoint, query)
+            .await
+            .and_then(|body| parse_body(&body.data))
+            .map_err(|errors| with_endpoint(errors, endpoint))
+    }
+
+    async fn data_body(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+        extra: &[(&str, &str)],
+    ) -> ApiResult<Cached<Bytes>> {
+        let key = self.key();
+        if key.trim().is_empty() {
+            return Err(vec![InternalError::internal(
+                "missing-key",
+                401,
+                "the client was created with an empty key",
+            )]);
+        }
+
+        let cache = self.cache.to_string();
+        let mut query = standard_query(&key, &cache, params);
+        if self.key_header.is_some() {
+            query.retain(|(name, _)| *name != "key");
+        }
+        query.extend_from_slice(extra);
+        self.request_body(endpoint, &query).await
+    }
+
+    async fn request_body(
+        &self,
+        endpoint: &str,
+        query: &[(&str, &str)],
+    ) -> ApiResult<Cached<Bytes>> {
+        let body = self.request_body_unspanned(endpoint, query);
+        #[cfg(feature = "tracing")]
+        let body = tracing::Instrument::instrument(
+            body,
+            tracing::info_span!(
+                "request",
+                endpoint,
+                otel.name = %self.span_name(endpoint, query)
+            ),
+        );
+        body.await
+    }
+
+    /// The name of the span a request runs in, from the query without the key.
+    #[cfg(feature = "tracing")]
+    fn span_name(&self, endpoint: &str, query: &[(&str, &str)]) -> String {
+        let params = query
+            .iter()
+            .filter(|(name, _)| *name != "key")
+            .copied()
+            .collect::<Vec<_>>();
+        match &self.span_name {
+            Some(span_name) => span_name(endpoint, &params),
+            None => endpoint.to_owned(),
+        }
+    }
+
+    async fn request_body_unspanned(
+        &self,
+        endpoint: &str,
+        query: &[(&str, &str)],
+    ) -> ApiResult<Cached<Bytes>> {
+        if let Some(enabled) = &*self.enabled_endpoints.lock().unwrap() {
+            if endpoint != "key" && !enabled.contains(endpoint) {
+                return Err(vec![InternalError::internal(
+                    "disabled-endpoint",
+                    403,
+                    format!("the client's key isn't permitted to use the {endpoint} endpoint"),
+                )]);
+            }
+        }
+        self.remaining(endpoint)?;
+        let http = match &self.transport {
+            Transport::Http(http) => http,
+            Transport::Stub(stubs) => {
+                return match stubs.get(endpoint) {
+                    Some(body) => Ok(Cached {
+                        data: Bytes::from(body.clone()),
+                        from_cache: false,
+                        rate_limit: None,
+                        warnings: Vec::new(),
+                        headers: reqwest::header::HeaderMap::new(),
+                    }),
+                    None => Err(vec![InternalError::internal(
+                        "missing-stub",
+                        404,
+                        format!("no stubbed response for the {endpoint} endpoint"),
+                    )]),
+                }
+            }
+        };
+
+        let request_key = request_key(endpoint, query);
+        let cached = self
+            .local_cache
+            .as_ref()
+            .and_then(|cache| cache.lock().unwrap().get(&request_key).cloned());
+        let ttl = self
+            .endpoint_cache_ttls
+            .get(endpoint)
+            .or(self.cache_ttl.as_ref());
+        if let (Some(ttl), Some(cached)) = (ttl, &cached) {
+            if cached.stored_at.elapsed() < *ttl {
+                return Ok(Cached {
+                    data: cached.body.clone(),
+                    from_cache: true,
+                    rate_limit: None,
+                    warnings: Vec::new(),
+                    headers: reqwest::header::HeaderMap::new(),
+                });
+            }
+        }
+        let _permit = match (&self.limiter, self.deadline) {
+            (Some(limiter), Some(deadline)) => Some(
+                tokio::time::timeout_at(deadline.into(), limiter.acquire(self.priority))
+                    .await
+                    .map_err(|_| deadline_exceeded(endpoint))?,
+            ),
+            (Some(limiter), None) => Some(limiter.acquire(self.priority).await),
+            (None, _) => None,
+        };
+        let remaining = self.remaining(endpoint)?;
+        if let Some(circuit_breaker) = &self.circuit_breaker {
+            circuit_breaker.permit()?;
+        }
+        let etag = cached.as_ref().and_then(|entry| entry.etag.as_deref());
+        let request = self.build_request(http, endpoint, query, etag, remaining)?;
+
+        let started = Instant::now();
+        let flight_key = format!("{request_key}\n{etag:?}\n{:?}", self.deadline);
+        let mut fetched = self.fetch(endpoint, request, &flight_key).await;
+        if let (Some(304), None) = (fetched.status, &cached) {
+            log_debug!(
+                "{} was answered with status 304 without a cached body, requesting it again",
+                request_label(endpoint, query)
+            );
+            let request =
+                self.build_request(http, endpoint, query, None, self.remaining(endpoint)?)?;
+            fetched = self.send_recorded(endpoint, request).await;
+        }
+        let elapsed = started.elapsed();
+        if let (Some(inspector), Some(status)) = (&self.inspector, fetched.status) {
+            let body = match &fetched.body {
+                Ok(body) => String::from_utf8_lossy(body),
+                Err(_) => Cow::Borrowed(""),
+            };
+            inspector(
+                &self.redacted_url(endpoint, query),
+                status,
+                &fetched.headers,
+                &body,
+            );
+        }
+        match &fetched.body {
+            Ok(_) => log_debug!(
+                "{} responded with status {:?} in {elapsed:?}",
+                request_label(endpoint, query),
+                fetched.status
+            ),
+            Err(errors) => log_warn!(
+                "{} failed in {elapsed:?}: {}",
+                request_label(endpoint, query),
+                summarize_errors(errors)
+            ),
+        }
+
+        if let (Some(304), Some(cached)) = (fetched.status, cached) {
+            if let Some(cache) = &self.local_cache {
+                if let Some(entry) = cache.lock().unwrap().get_mut(&request_key) {
+                    entry.stored_at = Instant::now();
+                }
+            }
+            return Ok(Cached {
+                data: cached.body,
+                from_cache: true,
+                rate_limit: fetched.rate_limit,
+                warnings: Vec::new(),
+                headers: fetched.headers,
+            });
+        }
+        if let (true, Some(status @ (..=199 | 300..))) = (self.trust_status_code, fetched.status) {
+            return Err(vec![InternalError::internal(
+                "unexpected-status",
+                status,
+                format!("the API responded with status {status}"),
+            )]);
+        }
+        let body = fetched.body?;
+        let cacheable = (fetched.etag.is_some() || ttl.is_some()) && succeeded(&body);
+        if let (Some(cache), Some(200), true) = (&self.local_cache, fetched.status, cacheable) {
+            let mut cache = cache.lock().unwrap();
+            if cache.len() >= self.local_cache_capacity && !cache.contains_key(&request_key) {
+                let oldest = cache
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.stored_at)
+                    .map(|(request_key, _)| request_key.clone());
+                if let Some(oldest) = oldest {
+                    cache.remove(&oldest);
+                }
+            }
+            cache.insert(
+                request_key,
+                CacheEntry {
+                    etag: fetched.etag,
+                    body: body.clone(),
+                    query: query
+                        .iter()
+                        .filter(|(name, _)| *name != "key")
+                        .map(|&(name, value)| (name.to_owned(), value.to_owned()))
+                        .collect(),
+                    shared: None,
+                    stored_at: Instant::now(),
+                },
+            );
+        }
+        Ok(Cached {
+            data: body,
+            from_cache: false,
+            rate_limit: fetched.rate_limit,
+            warnings: Vec::new(),
+            headers: fetched.headers,
+        })
+    }
+
+    /// Build a request to an endpoint with the client's timeout, key header and request hook, conditional on the
+    /// ETag of a cached body if given.
+    fn build_request(
+        &self,
+        http: &reqwest::Client,
+        endpoint: &str,
+        query: &[(&str, &str)],
+        etag: Option<&str>,
+        remaining: Option<Duration>,
+    ) -> ApiResult<reqwest::RequestBuilder> {
+        let mut request = http.get(self.endpoint_url(endpoint)).query(query);
+        if let Some(etag) = etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        let timeout = self
+            .endpoint_timeouts
+            .get(endpoint)
+            .or(self.timeout.as_ref());
+        if let Some(timeout) = match (timeout, remaining) {
+            (Some(timeout), Some(remaining)) => Some(remaining.min(*timeout)),
+            (timeout, remaining) => timeout.copied().or(remaining),
+        } {
+            request = request.timeout(timeout);
+        }
+        if let Some(name) = &self.key_header {
+            let mut value = reqwest::header::HeaderValue::from_str(&self.key()).map_err(|_| {
+                vec![InternalError::internal(
+                    "invalid-key",
+                    400,
+                    format!("the client's key can't be sent in the {name} header"),
+                )]
+            })?;
+            value.set_sensitive(true);
+            request = request.header(name, value);
+        }
+        if let Some(request_hook) = &self.request_hook {
+            request = request_hook(request);
+        }
+        Ok(request)
+    }
+
+    /// The time left until the deadline set with [`Client::with_deadline`], if any, failing once it has passed.
+    fn remaining(&self, endpoint: &str) -> ApiResult<Option<Duration>> {
+        match self.deadline {
+            Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                Some(remaining) if !remaining.is_zero() => Ok(Some(remaining)),
+                _ => Err(deadline_exceeded(endpoint)),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Send a request, sharing the response with concurrent requests with the same flight key when
+    /// [`ClientBuilder::coalesce_requests`] is enabled.
+    async fn fetch(
+        &self,
+        endpoint: &str,
+        request: reqwest::RequestBuilder,
+        flight_key: &str,
+    ) -> Fetched {
+        let in_flight = match &self.in_flight {
+            Some(in_flight) => in_flight,
+            None => return self.send_recorded(endpoint, request).await,
+        };
+
+        let flight = in_flight
+            .lock()
+            .unwrap()
+            .entry(flight_key.to_owned())
+            .or_insert_with(|| self.send_recorded(endpoint, request).shared())
+            .clone();
+
+        let result = flight.clone().await;
+        let mut in_flight = in_flight.lock().unwrap();
+        if in_flight
+            .get(flight_key)
+            .is_some_and(|current| current.ptr_eq(&flight))
+        {
+            in_flight.remove(flight_key);
+        }
+        result
+    }
+
+    /// Send a request, recording its outcome with the circuit breaker and [`ClientBuilder::on_request`] callback
+    /// once, however many callers share the response.
+    fn send_recorded(
+        &self,
+        endpoint: &str,
+        request: reqwest::RequestBuilder,
+    ) -> BoxFuture<'static, Fetched> {
+        let endpoint = endpoint.to_owned();
+        let max_response_bytes = self.max_response_bytes;
+        let circuit_breaker = self.circuit_breaker.clone();
+        let on_request = self.on_request.clone();
+        async move {
+            let started = Instant::now();
+            let fetched = send(request, max_response_bytes).await;
+            if let Some(circuit_breaker) = &circuit_breaker {
+                circuit_breaker
+                    .record(fetched.body.is_ok() && !matches!(fetched.status, Some(500..)));
+            }
+            if let Some(on_request) = &on_request {
+                on_request(&endpoint, fetched.status, started.elapsed());
+            }
+            fetched
+        }
+        .boxed()
+    }
+}
+
+/// The API's endpoints as a trait, so code depending on `&dyn HypixelApi` can swap the [`Client`] for a fake in tests.
+///
+/// # Examples
+///
+/// ```rust
+/// use cactive_hypixel_api::HypixelApi;
+///
+/// async fn staff_online(api: &dyn HypixelApi) -> usize {
+///     api.staff_tracker("online".to_owned()).await.map_or(0, |staff| staff.len())
+/// }
+/// ```
+#[async_trait]
+pub trait HypixelApi: Send + Sync {
+    async fn nickname_history(&self, nickname: String) -> ApiResult<Vec<NicknameHistory>>;
+
+    async fn player_data(&self, uuid: String) -> ApiResult<PlayerData>;
+
+    async fn staff_tracker(&self, filter: String) -> ApiResult<Vec<StaffTracker>>;
+
+    async fn punishment_data(&self, id: String) -> ApiResult<PunishmentData>;
+
+    async fn key_data(&self, key: String) -> ApiResult<KeyData>;
+}
+
+#[async_trait]
+impl HypixelApi for Client {
+    async fn nickname_history(&self, nickname: String) -> ApiResult<Vec<NicknameHistory>> {
+        Client::nickname_history(self, nickname).await
+    }
+
+    async fn player_data(&self, uuid: String) -> ApiResult<PlayerData> {
+        Client::player_data(self, uuid).await
+    }
+
+    async fn staff_tracker(&self, filter: String) -> ApiResult<Vec<StaffTracker>> {
+        Client::staff_tracker(self, filter).await
+    }
+
+    async fn punishment_data(&self, id: String) -> ApiResult<PunishmentData> {
+        Client::punishment_data(self, id).await
+    }
+
+    async fn key_data(&self, key: String) -> ApiResult<KeyData> {
+        Client::key_data(self, key).await
+    }
+}
+
+/// Where a client's requests are answered from.
+#[derive(Clone)]
+enum Transport {
+    Http(reqwest::Client),
+    /// Canned response bodies by endpoint, see [`Client::with_stub`].
+    Stub(Arc<HashMap<String, String>>),
+}
+
+#[derive(Clone)]
+struct Fetched {
+    status: Option<u16>,
+    etag: Option<String>,
+    headers: reqwest::header::HeaderMap,
+    rate_limit: Option<RateLimit>,
+    body: ApiResult<Bytes>,
+}
+
+/// The priority of a request waiting for a slot of [`ClientBuilder::max_concurrent_requests`], see
+/// [`Client::with_priority`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Priority {
+    /// Foreground work, such as a user looking up a player, which is sent before any waiting normal or low
+    /// priority request.
+    High,
+    /// The default for every request.
+    #[default]
+    Normal,
+    /// Background work, such as a large batch, which is only sent once no high or normal priority request is
+    /// waiting.
+    Low,
+}
+
+/// Limits how many requests are sent at a time, handing free slots to the highest priority waiting request.
+struct Limiter {
+    state: Mutex<LimiterState>,
+}
+
+struct LimiterState {
+    available: usize,
+    /// The requests waiting for a slot, indexed by [`Priority`] from highest to lowest, each in arrival order.
+    waiting: [VecDeque<oneshot::Sender<Permit>>; 3],
+}
+
+/// A slot of a [`Limiter`], handed to the next waiting request when dropped.
+struct Permit {
+    limiter: Option<Arc<Limiter>>,
+}
+
+impl Limiter {
+    async fn acquire(self: &Arc<Self>, priority: Priority) -> Permit {
+        let receiver = {
+            let mut state = self.state.lock().unwrap();
+            if state.available > 0 {
+                state.available -= 1;
+                return Permit {
+                    limiter: Some(self.clone()),
+                };
+            }
+            let (sender, receiver) = oneshot::channel();
+            state.waiting[priority as usize].push_back(sender);
+            receiver
+        };
+        receiver.await.unwrap_or(Permit { limiter: None })
+    }
+
+    /// Hand a freed slot to the highest priority request still waiting, skipping any that were cancelled, or make
+    /// it available again if none are.
+    fn release(self: &Arc<Self>) {
+        loop {
+            let sender = {
+                let mut state = self.state.lock().unwrap();
+                match state.waiting.iter_mut().find_map(VecDeque::pop_front) {
+                    Some(sender) => sender,
+                    None => {
+                        state.available += 1;
+                        return;
+                    }
+                }
+            };
+            let permit = Permit {
+                limiter: Some(self.clone()),
+            };
+            match sender.send(permit) {
+                Ok(()) => return,
+                Err(mut permit) => permit.limiter = None,
+            }
+        }
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        if let Some(limiter) = self.limiter.take() {
+            limiter.release();
+        }
+    }
+}
+
+/// Short-circuits requests after consecutive failures, see [`ClientBuilder::circuit_breaker`].
+struct CircuitBreaker {
+    failures: u32,
+    window: Duration,
+    cooldown: Duration,
+    state: Mutex<CircuitState>,
+}
+
+#[derive(Default)]
+struct CircuitState {
+    failures: u32,
+    first_failure: Option<Instant>,
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    /// Allow a request unless the circuit is open. Once the cooldown is over one probe request is let through,
+    /// keeping the circuit open for the others until it succeeds, or for another cooldown if it is dropped.
+    fn permit(&self) -> ApiResult<()> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        match state.open_until {
+            Some(open_until) if now < open_until => Err(vec![InternalError::internal(
+                "circuit-open",
+                503,
+                format!(
+                    "requests are paused for {:?} after {} consecutive failures",
+                    open_until - now,
+                    self.failures
+                ),
+            )]),
+            Some(_) => {
+                state.open_until = Some(now + self.cooldown);
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn record(&self, succeeded: bool) {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        if succeeded {
+            *state = CircuitState::default();
+            return;
+        }
+        if state.open_until.is_some() {
+            state.open_until = Some(now + self.cooldown);
+            return;
+        }
+
+        if state
+            .first_failure
+            .is_none_or(|first_failure| now - first_failure > self.window)
+        {
+            state.failures = 0;
+            state.first_failure = Some(now);
+        }
+        state.failures += 1;
+        if state.failures >= self.failures {
+            log_warn!(
+                "pausing requests for {:?} after {} consecutive failures",
+                self.cooldown,
+                state.failures
+            );
+            state.open_until = Some(now + self.cooldown);
+        }
+    }
+}
+
+/// The number of entries [`ClientBuilder::local_cache`] holds unless set with
+/// [`ClientBuilder::local_cache_capacity`].
+const DEFAULT_LOCAL_CACHE_CAPACITY: usize = 1024;
+
+#[derive(Clone)]
+struct CacheEntry {
+    etag: Option<String>,
+    body: Bytes,
+    /// The request's query parameters without the key, for [`Client::invalidate_player`].
+    query: Vec<(String, String)>,
+    /// The body decoded by a `*_shared` method and its warnings, handed out again while the body is unchanged.
+    shared: Option<(Arc<dyn Any + Send + Sync>, Vec<InternalError>)>,
+    /// When the body was received or last revalidated, for [`ClientBuilder::cache_ttl`].
+    stored_at: Instant,
+}
+
+/// Whether a body is a successful envelope, so an API error answered with status 200 isn't cached and replayed.
+fn succeeded(body: &[u8]) -> bool {
+    #[derive(Deserialize)]
+    struct Envelope {
+        success: bool,
+    }
+
+    serde_json::from_slice::<Envelope>(body).is_ok_and(|envelope| envelope.success)
+}
+
+async fn send(request: reqwest::RequestBuilder, max_bytes: Option<usize>) -> Fetched {
+    let mut request = match request.send().await {
+        Ok(req) => req,
+        Err(err) => {
+            return Fetched {
+                status: None,
+                etag: None,
+                headers: reqwest::header::HeaderMap::new(),
+                rate_limit: None,
+                body: Err(vec![err.into()]),
+            }
+        }
+    };
+    let status = request.status();
+    let etag = request
+        .headers()
+        .get(reqwest::header::ETAG)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_owned);
+    let rate_limit = rate_limit(request.headers());
+    let headers = request.headers().clone();
+
+    let content_type = request
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned());
+    if let Some(content_type) = content_type.filter(|value| !value.contains("json")) {
+        return Fetched {
+            status: Some(status.as_u16()),
+            etag,
+            headers,
+            rate_limit,
+            body: Err(vec![InternalError::internal(
+                "unexpected-content-type",
+                status.as_u16(),
+                format!("expected a JSON response but received {content_type} ({status})"),
+            )]),
+        };
+    }
+
+    let too_large = || {
+        vec![InternalError::internal(
+            "response-too-large",
+            413,
+            format!(
+                "the response exceeded the limit of {} bytes",
+                max_bytes.unwrap_or_default()
+            ),
+        )]
+    };
+    let max_bytes = match max_bytes {
+        Some(max_bytes) => max_bytes,
+        None => {
+            return Fetched {
+                status: Some(status.as_u16()),
+                etag,
+                headers,
+                rate_limit,
+                body: request.bytes().await.map_err(|err| vec![err.into()]),
+            }
+        }
+    };
+    if request
+        .content_length()
+        .is_some_and(|length| length > max_bytes as u64)
+    {
+        return Fetched {
+            status: Some(status.as_u16()),
+            etag,
+            headers,
+            rate_limit,
+            body: Err(too_large()),
+        };
+    }
+
+    let mut body = Vec::new();
+    let body = loop {
+        match request.chunk().await {
+            Ok(Some(chunk)) if body.len() + chunk.len() > max_bytes => break Err(too_large()),
+            Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+            Ok(None) => break Ok(Bytes::from(body)),
+            Err(err) => break Err(vec![err.into()]),
+        }
+    };
+    Fetched {
+        status: Some(status.as_u16()),
+        etag,
+        headers,
+        rate_limit,
+        body,
+    }
+}
+
+/// Read the `X-RateLimit-Limit`, `X-RateLimit-Remaining` and `X-RateLimit-Reset` headers, or `None` if none of
+/// them were sent.
+fn rate_limit(headers: &reqwest::header::HeaderMap) -> Option<RateLimit> {
+    let header = |name| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+    };
+    let rate_limit = RateLimit {
+        limit: header("x-ratelimit-limit"),
+        remaining: header("x-ratelimit-remaining"),
+        reset: header("x-ratelimit-reset").map(Duration::from_secs),
+    };
+    (rate_limit != RateLimit::default()).then_some(rate_limit)
+}
+
+impl ClientBuilder {
+    /// Set whether the API should serve smart-cached (semi-accurate) data.
+    pub fn cache(mut self, cache: bool) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Set the base URL requests are sent to, such as a mock server, defaulting to the public API for
+    /// [`API_VERSION`]. The URL should serve that same version, as [`Client::api_version`] reports it regardless.
+    ///
+    /// Trailing slashes are stripped, so `https://host/api/v3/` and `https://host/api/v3` are equivalent.
+    pub fn base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_owned();
+        self
+    }
+
+    /// Only use HTTP/2, skipping the HTTP/1.1 upgrade negotiation.
+    pub fn http2_prior_knowledge(mut self, enabled: bool) -> Self {
+        self.http2_prior_knowledge = enabled;
+        self
+    }
+
+    /// Set how long idle pooled connections are kept alive, defaulting to reqwest's timeout.
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Set a timeout for every request, from sending it until the whole body has been received, which fails
+    /// with a `timeout` error when exceeded. Requests have no timeout by default. This includes the time taken
+    /// to connect, which can be limited separately with [`ClientBuilder::connect_timeout`].
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set a timeout for establishing each connection to the API, including the TLS handshake, which fails with a
+    /// `timeout` error when exceeded. There is no connect timeout by default, leaving it to the operating system.
+    ///
+    /// A short connect timeout fails fast when the API is unreachable, while [`ClientBuilder::timeout`] can still
+    /// allow slow responses. Requests reusing a pooled connection are unaffected, as they have nothing to connect.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let client = Client::builder(key)
+    ///     .connect_timeout(Duration::from_secs(2))
+    ///     .timeout(Duration::from_secs(30))
+    ///     .build()?;
+    /// ```
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Set a timeout for requests to one endpoint, such as `"player-data"`, taking precedence over
+    /// [`ClientBuilder::timeout`] for that endpoint, which the remaining endpoints still fall back to.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let client = Client::builder(key)
+    ///     .timeout(Duration::from_secs(5))
+    ///     .endpoint_timeout("player-data", Duration::from_secs(20))
+    ///     .build()?;
+    /// ```
+    pub fn endpoint_timeout(mut self, endpoint: &str, timeout: Duration) -> Self {
+        self.endpoint_timeouts.insert(endpoint.to_owned(), timeout);
+        self
+    }
+
+    /// Share a single HTTP call between concurrent identical requests, rather than sending each one.
+    ///
+    /// Requests are identical when their endpoint and query parameters, excluding the key, are the same, as are
+    /// the ETag of their cached body, if any, and their [`Client::with_deadline`] deadline, so every caller gets
+    /// the response its own request would have.
+    pub fn coalesce_requests(mut self, enabled: bool) -> Self {
+        self.coalesce_requests = enabled;
+        self
+    }
+
+    /// Register a callback invoked after each HTTP request with the endpoint, the response status (`None` if
+    /// no response was received) and the elapsed time, for recording metrics.
+    ///
+    /// The callback runs on the task making the request, so it should be cheap and never block.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let client = Client::builder(key)
+    ///     .on_request(|endpoint, status, elapsed| {
+    ///         println!("{endpoint} responded {status:?} in {}ms", elapsed.as_millis())
+    ///     })
+    ///     .build()?;
+    /// ```
+    pub fn on_request<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&str, Option<u16>, Duration) + Send + Sync + 'static,
+    {
+        self.on_request = Some(Arc::new(callback));
+        self
+    }
+
+    /// Abort reading any response body larger than the given number of bytes with a `response-too-large`
+    /// error, rather than buffering it. Responses are unlimited by default.
+    pub fn max_response_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_response_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Keep responses in a local cache shared between clones of the client, separately from the API's own
+    /// smart cache.
+    ///
+    /// Responses with an `ETag` header are stored and revalidated with `If-None-Match`, so a `304 Not Modified`
+    /// response is answered from the cache without downloading the body again. This is a no-op if the
+    /// server doesn't emit `ETag` headers.
+    ///
+    /// An entry is written in a single step once the whole body has been received, so dropping a request
+    /// future, such as on a timeout, never leaves a partial entry behind: the cache simply isn't updated.
+    ///
+    /// The cache holds at most 1024 entries by default, see [`ClientBuilder::local_cache_capacity`].
+    pub fn local_cache(mut self, enabled: bool) -> Self {
+        self.local_cache = enabled;
+        self
+    }
+
+    /// Set how many responses the local cache holds before evicting the one stored or revalidated longest ago,
+    /// which defaults to 1024 and is at least 1. Has no effect unless [`ClientBuilder::local_cache`] is enabled.
+    pub fn local_cache_capacity(mut self, entries: usize) -> Self {
+        self.local_cache_capacity = entries;
+        self
+    }
+
+    /// Answer requests from the local cache without contacting the API while their entry is younger than the given
+    /// time to live, trading freshness for fewer requests. Has no effect unless [`ClientBuilder::local_cache`] is
+    /// enabled.
+    ///
+    /// By default entries have no time to live, so every request is sent and only revalidated with its `ETag`.
+    /// With one, responses without an `ETag` are cached too, and once an entry is older than its time to live it
+    /// is revalidated or fetched again as usual, restarting its time to live. Answers served without a request
+    /// have no rate limit or warnings.
+    ///
+    /// Use [`ClientBuilder::endpoint_cache_ttl`] to tune volatile and stable endpoints separately. Reasonable
+    /// starting points are a few seconds for `staff-tracker`, whose online statuses change constantly, a minute
+    /// for `player-data`, several minutes for `nickname-history`, and an hour or more for `punishment-data`, as a
+    /// punishment rarely changes once issued.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let client = Client::builder(key)
+    ///     .local_cache(true)
+    ///     .cache_ttl(Duration::from_secs(60))
+    ///     .endpoint_cache_ttl("staff-tracker", Duration::from_secs(5))
+    ///     .endpoint_cache_ttl("punishment-data", Duration::from_secs(3600))
+    ///     .build()?;
+    /// ```
+    pub fn cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Set the time to live of cached responses from one endpoint, such as `"staff-tracker"`, taking precedence
+    /// over [`ClientBuilder::cache_ttl`] for that endpoint, which the remaining endpoints still fall back to.
+    pub fn endpoint_cache_ttl(mut self, endpoint: &str, ttl: Duration) -> Self {
+        self.endpoint_cache_ttls.insert(endpoint.to_owned(), ttl);
+        self
+    }
+
+    /// Send at most the given number of requests at a time across the client and its clones, queueing the rest
+    /// until a slot is free. Requests are unlimited by default.
+    ///
+    /// A free slot goes to the longest waiting request of the highest [`Priority`], set with
+    /// [`Client::with_priority`], so low priority requests may wait indefinitely while higher priority ones keep
+    /// arriving. A request dropped while waiting gives up its place in the queue.
+    pub fn max_concurrent_requests(mut self, requests: usize) -> Self {
+        self.max_concurrent_requests = Some(requests);
+        self
+    }
+
+    /// Stop sending requests for `cooldown` once `failures` consecutive requests have failed within `window`,
+    /// failing them immediately with a `circuit-open` error instead, to avoid hammering the API during an outage.
+    ///
+    /// A request fails if no response was received or its status was 500 or above. After the cooldown a single
+    /// probe request is sent while the others keep failing: the circuit closes once it succeeds, and stays open
+    /// for another cooldown if it fails. Any successful request resets the count. The breaker is shared by the
+    /// client's clones, and requests answered without contacting the API are unaffected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let client = Client::builder(key)
+    ///     .circuit_breaker(5, Duration::from_secs(30), Duration::from_secs(60))
+    ///     .build()?;
+    /// ```
+    pub fn circuit_breaker(mut self, failures: u32, window: Duration, cooldown: Duration) -> Self {
+        self.circuit_breaker = Some((failures, window, cooldown));
+        self
+    }
+
+    /// Trim and lowercase nicknames before looking up their history, including through
+    /// [`Client::player_data_by_nickname`], so differently cased input resolves to the same request.
+    ///
+    /// Disabled by default, sending nicknames exactly as given. Coalescing and the local cache key on the
+    /// normalized nickname, so `"Caykey "` and `"caykey"` share their entries.
+    pub fn normalize_nicknames(mut self, enabled: bool) -> Self {
+        self.normalize_nicknames = enabled;
+        self
+    }
+
+    /// Treat every response with a status outside 200-299 as an `unexpected-status` error carrying that status,
+    /// whatever its body says.
+    ///
+    /// By default the status is ignored and the body's `success` field alone decides the outcome, so a `500`
+    /// with `success: true` is returned as data. With this enabled, a non-2xx status fails before the body is
+    /// read, and a 2xx response still fails if `success` is false. A `304 Not Modified` answered from the
+    /// local cache is unaffected.
+    pub fn trust_status_code(mut self, enabled: bool) -> Self {
+        self.trust_status_code = enabled;
+        self
+    }
+
+    /// Send the key in the given header, such as `x-api-key`, instead of the `key` query parameter, so it doesn't
+    /// end up in the access logs of servers and proxies along the way.
+    ///
+    /// The public API only documents the `key` query parameter, which remains the default, so only enable this
+    /// for a server or proxy known to accept the key in that header: the public API would reject the request with
+    /// `no-authentication`. The `key` endpoint still sends the key being looked up as its query parameter.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use reqwest::header::HeaderName;
+    ///
+    /// let client = Client::builder(key)
+    ///     .base_url("https://hypixel-proxy.example.com/api/v3".to_owned())
+    ///     .key_header(HeaderName::from_static("x-api-key"))
+    ///     .build()?;
+    /// ```
+    pub fn key_header(mut self, name: reqwest::header::HeaderName) -> Self {
+        self.key_header = Some(name);
+        self
+    }
+
+    /// Register a hook which can modify every outgoing request just before it is sent, such as to add tracing
+    /// headers or signatures.
+    ///
+    /// The hook runs after the key, cache and endpoint parameters and any conditional headers have been added,
+    /// so it can see and override them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let client = Client::builder(key)
+    ///     .request_hook(|request| request.header("x-trace-id", "abc"))
+    ///     .build()?;
+    /// ```
+    pub fn request_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(reqwest::RequestBuilder) -> reqwest::RequestBuilder + Send + Sync + 'static,
+    {
+        self.request_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Register an inspector invoked with the URL, status, headers and body text of every response before it is
+    /// deserialized, for dumping raw traffic while debugging without an intercepting proxy.
+    ///
+    /// The key is replaced with `REDACTED` in the URL, as in [`Client::build_url`]. The body is empty when it
+    /// wasn't read, such as when it wasn't JSON or exceeded `max_response_bytes`. Copying the headers and decoding
+    /// every body adds overhead to each request, so this is intended for debugging rather than production.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let client = Client::builder(key)
+    ///     .inspect_responses(|url, status, headers, body| {
+    ///         eprintln!("{url} responded {status} with {headers:?}: {body}")
+    ///     })
+    ///     .build()?;
+    /// ```
+    pub fn inspect_responses<F>(mut self, inspector: F) -> Self
+    where
+        F: Fn(&str, u16, &reqwest::header::HeaderMap, &str) + Send + Sync + 'static,
+    {
+        self.inspector = Some(Arc::new(inspector));
+        self
+    }
+
+    /// Name the `request` span each request runs in, from its endpoint and query parameters, such as to include
+    /// the UUID being looked up. Spans are named after their endpoint by default.
+    ///
+    /// The name is recorded in the span's `otel.name` field, which OpenTelemetry exporters use as the span name,
+    /// alongside an `endpoint` field. The key is removed from the parameters before they are passed in, so it
+    /// can never end up in a span, including the key looked up by [`Client::key_data`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let client = Client::builder(key)
+    ///     .span_name(|endpoint, params| match params.iter().find(|(name, _)| *name == "uuid") {
+    ///         Some((_, uuid)) => format!("{endpoint} {uuid}"),
+    ///         None => endpoint.to_owned(),
+    ///     })
+    ///     .build()?;
+    /// ```
+    #[cfg(feature = "tracing")]
+    pub fn span_name<F>(mut self, span_name: F) -> Self
+    where
+        F: Fn(&str, &[(&str, &str)]) -> String + Send + Sync + 'static,
+    {
+        self.span_name = Some(Arc::new(span_name));
+        self
+    }
+
+    /// Accept any TLS certificate, including self-signed and expired ones, such as for a local HTTPS mock server.
+    ///
+    /// This is dangerous and only meant for testing: it lets anyone intercept requests, including the key.
+    /// Building a client with it enabled emits a warning through `tracing` or `log`, whichever features are
+    /// enabled, or through both when both are. With neither feature enabled, no warning is emitted.
+    #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+    pub fn danger_accept_invalid_certs(mut self, enabled: bool) -> Self {
+        self.danger_accept_invalid_certs = enabled;
+        self
+    }
+
+    /// Build the client, failing if the underlying HTTP client cannot be initialised.
+    pub fn build(self) -> ApiResult<Client> {
+        let mut http = reqwest::Client::builder();
+        if self.http2_prior_knowledge {
+            http = http.http2_prior_knowledge();
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            http = http.pool_idle_timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            http = http.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.timeout {
+            http = http.timeout(timeout);
+        }
+        #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+        if self.danger_accept_invalid_certs {
+            log_warn!(
+                "TLS certificate verification is disabled, which must never be used in production"
+            );
+            http = http.danger_accept_invalid_certs(true);
+        }
+
+        let http = http.build().map_err(|err| vec![err.into()])?;
+        Ok(self.build_with(Transport::Http(http)))
+    }
+
+    fn build_with(self, transport: Transport) -> Client {
+        Client {
+            key: Arc::new(Mutex::new(self.key)),
+            cache: self.cache,
+            base_url: self.base_url,
+            transport,
+            key_data: Arc::default(),
+            enabled_endpoints: Arc::default(),
+            in_flight: self.coalesce_requests.then(Arc::default),
+            on_request: self.on_request,
+            max_response_bytes: self.max_response_bytes,
+            local_cache: self.local_cache.then(Arc::default),
+            local_cache_capacity: self.local_cache_capacity.max(1),
+            request_hook: self.request_hook,
+            inspector: self.inspector,
+            normalize_nicknames: self.normalize_nicknames,
+            trust_status_code: self.trust_status_code,
+            endpoint_timeouts: Arc::new(self.endpoint_timeouts),
+            timeout: self.timeout,
+            deadline: None,
+            key_header: self.key_header,
+            #[cfg(feature = "tracing")]
+            span_name: self.span_name,
+            cache_ttl: self.cache_ttl,
+            endpoint_cache_ttls: Arc::new(self.endpoint_cache_ttls),
+            circuit_breaker: self.circuit_breaker.map(|(failures, window, cooldown)| {
+                Arc::new(CircuitBreaker {
+                    failures: failures.max(1),
+                    window,
+                    cooldown,
+                    state: Mutex::default(),
+                })
+            }),
+            limiter: self.max_concurrent_requests.map(|permits| {
+                Arc::new(Limiter {
+                    state: Mutex::new(LimiterState {
+                        available: permits.max(1),
+                        waiting: Default::default(),
+                    }),
+                })
+            }),
+            priority: Priority::Normal,
+        }
+    }
+}
+
+fn deadline_exceeded(endpoint: &str) -> Vec<InternalError> {
+    vec![InternalError::internal(
+        "deadline-exceeded",
+        504,
+        format!("the deadline passed before the {endpoint} request was sent"),
+    )]
+}
+
+/// Identify a request by its endpoint and query parameters, for sharing and caching responses. The client's own
+/// key doesn't change the response, so it is excluded, except on the `key` endpoint, where it is the key being
+/// looked up. The result can hold a key, so it must never be logged, see [`request_label`].
+fn request_key(endpoint: &str, query: &[(&str, &str)]) -> String {
+    query
+        .iter()
+        .filter(|(name, _)| *name != "key" || endpoint == "key")
+        .fold(endpoint.to_owned(), |request_key, (name, value)| {
+            format!("{request_key}&{name}={value}")
+        })
+}
+
+/// Identify a request by its endpoint and query parameters for logging, excluding the key.
+fn request_label(endpoint: &str, query: &[(&str, &str)]) -> String {
+    query
+        .iter()
+        .filter(|(name, _)| *name != "key")
+        .fold(endpoint.to_owned(), |label, (name, value)| {
+            format!("{label}&{name}={value}")
+        })
+}
+
+/// Whether any `uuid` field in a cached response's data is the compact UUID.
+fn references_player(body: &[u8], uuid: &str) -> bool {
+    fn contains_uuid(value: &serde_json::Value, uuid: &str) -> bool {
+        match value {
+            serde_json::Value::Object(fields) => fields.iter().any(|(name, value)| {
+                (name == "uuid"
+                    && value
+                        .as_str()
+                        .is_some_and(|value| compact_uuid(value) == uuid))
+                    || contains_uuid(value, uuid)
+            }),
+            serde_json::Value::Array(values) => {
+                values.iter().any(|value| contains_uuid(value, uuid))
+            }
+            _ => false,
+        }
+    }
+
+    serde_json::from_slice::<serde_json::Value>(body)
+        .is_ok_and(|body| contains_uuid(&body["data"], uuid))
+}
+
+/// A UUID without dashes and in lowercase, so differently formatted UUIDs compare equal.
+fn compact_uuid(uuid: &str) -> String {
+    uuid.replace('-', "").to_lowercase()
+}
+
+fn standard_query<'a>(
+    key: &'a str,
+    cache: &'a str,
+    params: &[(&'a str, &'a str)],
+) -> Vec<(&'a str, &'a str)> {
+    let mut query = vec![("key", key), ("cache", cache)];
+    query.extend_from_slice(params);
+    query
+}
+
+/// Tell timeouts and DNS, TCP and TLS failures apart. reqwest only reports that connecting failed, so this
+/// relies on hyper's connect error messages, and treats any other connect failure to an `https` URL as the TLS
+/// handshake failing after the TCP connection was made. A body cut short by the connection dropping is an
+/// `incomplete-response` rather than a decode error, so it can be told apart from a schema mismatch.
+fn network_error_kind(error: &reqwest::Error) -> (&'static str, u16) {
+    if error.is_timeout() {
+        return ("timeout", 504);
+    }
+    if error.is_body() {
+        return ("incomplete-response", 502);
+    }
+    if !error.is_connect() {
+        return ("failed-api-request", 500);
+    }
+
+    let mut source = error.source();
+    while let Some(cause) = source {
+        let message = cause.to_string();
+        if message.starts_with("dns error") {
+            return ("dns-error", 502);
+        }
+        if message.starts_with("tcp ") {
+            return ("connect-error", 502);
+        }
+        source = cause.source();
+    }
+    match error.url().is_some_and(|url| url.scheme() == "https") {
+        true => ("tls-error", 502),
+        false => ("connect-error", 502),
+    }
+}
+
+fn punishment_id(id: &str) -> ApiResult<PunishmentId> {
+    id.parse()
+        .map_err(|error| with_endpoint(vec![error], "punishment-data"))
+}
+
+fn with_endpoint(mut errors: Vec<InternalError>, endpoint: &str) -> Vec<InternalError> {
+    for error in errors.iter_mut().filter(|error| error.endpoint.is_empty()) {
+        error.endpoint = endpoint.to_owned();
+    }
+    errors
+}
+
+fn parse_body<'a, T: Deserialize<'a>>(body: &'a [u8]) -> ApiResult<T> {
+    parse_body_with_warnings(body).map(|(data, _)| data)
+}
+
+fn parse_body_with_warnings<'a, T: Deserialize<'a>>(
+    body: &'a [u8],
+) -> ApiResult<(T, Vec<InternalError>)> {
+    match serde_json::from_slice::<APIData<T>>(body) {
+        Ok(json) => map_errors(json),
+        Err(err) => Err(vec![err.into()]),
+    }
+}
+
+/// Split a response into its data and the errors sent alongside it, or fail with those errors if it
+/// wasn't successful.
+fn map_errors<T>(json: APIData<T>) -> ApiResult<(T, Vec<InternalError>)> {
+    let error = |r#type: &str, message: &str| InternalError {
+        request_id: Some(json.id.clone()),
+        ..InternalError::internal(r#type, 500, message)
+    };
+
+    let with_request_id = |errors: Vec<APIError>| {
+        errors
+            .into_iter()
+            .map(|error| InternalError {
+                request_id: Some(json.id.clone()),
+                ..error.into()
+            })
+            .collect()
+    };
+    match (json.success, json.data, json.errors) {
+        (true, Some(data), errors) => Ok((data, errors.map_or_else(Vec::new, with_request_id))),
+        (true, None, _) => Err(vec![error(
+            "missing-data",
+            "the API reported success without any data",
+        )]),
+        (false, _, Some(errors)) if !errors.is_empty() => Err(with_request_id(errors)),
+        (false, _, _) => Err(vec![error(
+            "unexpected-error",
+            "the API reported failure without any errors",
+        )]),
+    }
+}
+
+#[tokio::test]
+async fn nickname_history_test() {
+    let client = Client::new("key".to_owned(), false);
+    match client.nickname_history("k".to_owned()).await {
+        Ok(_) => println!("Success"),
+        Err(error) => println!("Error {}", error[0].message),
+    }
+}
+
+#[tokio::test]
+async fn key_data_test() {
+    let client = Client::new("key".to_owned(), false);
+    match client.key_data("api".to_owned()).await {
+        Ok(data) => println!("Success {}", data.endpoints[0].id),
+        Err(error) => println!("Error {}", error[0].message),
+    }
+}
+
+#[tokio::test]
+async fn stub_test() {
+    let client = Client::with_stub(HashMap::from([(
+        "staff-tracker".to_owned(),
+        r#"{"success":true,"id":"stub","data":[{"uuid":"a","rank":"ADMIN","online":true}]}"#
+            .to_owned(),
+    )]));
+
+    let staff = client.staff_tracker("all".to_owned()).await.unwrap();
+    assert_eq!(staff[0].rank, "ADMIN");
+
+    match client.player_data("a".to_owned()).await {
+        Ok(_) => panic!("expected a missing stub"),
+        Err(error) => assert_eq!(error[0].r#type, "missing-stub"),
+    }
+}
+
+#[tokio::test]
+async fn cancellation_test() {
+    use tokio::io::AsyncReadExt;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let client = Client::builder("key".to_owned())
+        .base_url(format!("http://{}", listener.local_addr().unwrap()))
+        .build()
+        .unwrap();
+
+    let server = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buffer = [0; 1024];
+        while socket.read(&mut buffer).await.unwrap() > 0 {}
+    });
+
+    let request = client.player_data("uuid".to_owned());
+    assert!(tokio::time::timeout(Duration::from_millis(100), request)
+        .await
+        .is_err());
+
+    tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .expect("connection was not closed after the request was dropped")
+        .unwrap();
+}
+
+#[test]
+fn base_url_test() {
+    for base_url in ["http://localhost/api/v3", "http://localhost/api/v3/"] {
+        let client = Client::builder("key".to_owned())
+            .base_url(base_url.to_owned())
+            .build()
+            .unwrap();
+        assert_eq!(
+            client.endpoint_url("nickname-history"),
+            "http://localhost/api/v3/nickname-history"
+        );
+    }
+}
+
+#[cfg(test)]
+fn http_response(status: &str, content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {status}\r\ncontent-type: {content_type}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+#[cfg(test)]
+async fn mock_server(response: String) -> (String, tokio::sync::mpsc::UnboundedReceiver<String>) {
+    mock_server_with(move |_| response.clone()).await
+}
+
+#[cfg(test)]
+async fn mock_server_with<F>(handler: F) -> (String, tokio::sync::mpsc::UnboundedReceiver<String>)
+where
+    F: Fn(&str) -> String + Send + 'static,
+{
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut buffer = [0; 4096];
+            let length = socket.read(&mut buffer).await.unwrap_or(0);
+            let request = String::from_utf8_lossy(&buffer[..length]).into_owned();
+            let response = handler(&request);
+            let _ = sender.send(request);
+            let _ = socket.write_all(response.as_bytes()).await;
+        }
+    });
+    (format!("http://{address}"), receiver)
+}
+
+#[tokio::test]
+async fn unexpected_content_type_test() {
+    let (base_url, _) =
+        mock_server(http_response("403 Forbidden", "text/html", "<html></html>")).await;
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .build()
+        .unwrap();
+
+    match client.player_data("uuid".to_owned()).await {
+        Ok(_) => panic!("expected an unexpected content type"),
+        Err(error) => {
+            assert_eq!(error[0].r#type, "unexpected-content-type");
+            assert_eq!(error[0].code, 403);
+        }
+    }
+}
+
+#[tokio::test]
+async fn can_use_test() {
+    let client = Client::with_stub(HashMap::from([(
+        "key".to_owned(),
+        r#"{"success":true,"id":"stub","data":{"key":"k","valid":true,"active":true,"endpoints":[
+            {"id":"player-data","version":3,"status":true},
+            {"id":"staff-tracker","version":3,"status":false}
+        ]}}"#
+            .to_owned(),
+    )]));
+
+    assert!(client.can_use("player-data").await.unwrap());
+    assert!(!client.can_use("staff-tracker").await.unwrap());
+    assert!(!client.can_use("nickname-history").await.unwrap());
+}
+
+#[tokio::test]
+async fn get_value_test() {
+    let client = Client::with_stub(HashMap::from([(
+        "player-data".to_owned(),
+        r#"{"success":true,"id":"stub","data":{"uuid":"a","rank":"MVP_PLUS"}}"#.to_owned(),
+    )]));
+
+    let data = client
+        .get_value("player-data", &[("uuid", "a")])
+        .await
+        .unwrap();
+    assert_eq!(data["rank"], "MVP_PLUS");
+}
+
+#[tokio::test]
+async fn extra_params_test() {
+    let (base_url, mut requests) = mock_server(http_response(
+        "400 Bad Request",
+        "application/json",
+        r#"{"success":false,"id":"a","errors":[]}"#,
+    ))
+    .await;
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .build()
+        .unwrap();
+
+    let _ = client
+        .player_data_with("uuid".to_owned(), &[("fields", "tracker,ip history")])
+        .await;
+    let request = requests.recv().await.unwrap();
+    assert!(request.starts_with(
+        "GET /player-data?key=key&cache=false&uuid=uuid&fields=tracker%2Cip+history "
+    ));
+}
+
+#[test]
+fn build_url_test() {
+    let client = Client::new("secret".to_owned(), true);
+    let url = client.build_url("nickname-history", &[("nickname", "a b&c")]);
+
+    assert_eq!(
+        url,
+        format!(
+            "{}/nickname-history?key=REDACTED&cache=true&nickname=a+b%26c",
+            default_base_url()
+        )
+    );
+    assert!(!url.contains("secret"));
+}
+
+#[test]
+fn api_version_test() {
+    let client = Client::new("key".to_owned(), false);
+    assert_eq!(client.api_version(), API_VERSION);
+    assert!(client.build_url("key", &[]).starts_with(&format!(
+        "https://hypixel.cactive.network/api/v{API_VERSION}/key?"
+    )));
+}
+
+#[tokio::test]
+async fn coalesce_requests_test() {
+    let (base_url, mut requests) = mock_server(http_response(
+        "200 OK",
+        "application/json",
+        r#"{"success":true,"id":"a","data":[{"uuid":"a","rank":"ADMIN","online":true}]}"#,
+    ))
+    .await;
+    let sent = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = sent.clone();
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .coalesce_requests(true)
+        .on_request(move |_, _, _| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        })
+        .build()
+        .unwrap();
+
+    let (first, second) = tokio::join!(
+        client.staff_tracker("all".to_owned()),
+        client.staff_tracker("all".to_owned())
+    );
+    assert_eq!(first.unwrap(), second.unwrap());
+    assert_eq!(sent.load(std::sync::atomic::Ordering::SeqCst), 1);
+    assert!(requests.recv().await.is_some());
+    assert!(requests.try_recv().is_err());
+
+    let bounded = client.with_deadline(Instant::now() + Duration::from_secs(60));
+    let (first, second) = tokio::join!(
+        client.staff_tracker("all".to_owned()),
+        bounded.staff_tracker("all".to_owned())
+    );
+    assert_eq!(first.unwrap(), second.unwrap());
+    assert!(requests.recv().await.is_some());
+    assert!(requests.recv().await.is_some());
+}
+
+#[tokio::test]
+async fn not_modified_without_cache_test() {
+    let attempts = std::sync::atomic::AtomicUsize::new(0);
+    let (base_url, mut requests) = mock_server_with(move |_| {
+        match attempts.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+            0 => http_response("304 Not Modified", "application/json", ""),
+            _ => http_response(
+                "200 OK",
+                "application/json",
+                r#"{"success":true,"id":"a","data":{"uuid":"a","tracker":{}}}"#,
+            ),
+        }
+    })
+    .await;
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .build()
+        .unwrap();
+
+    assert_eq!(client.player_data("a".to_owned()).await.unwrap().uuid, "a");
+    assert!(requests.recv().await.is_some());
+    assert!(requests.recv().await.is_some());
+}
+
+#[tokio::test]
+async fn coalesce_key_data_test() {
+    let (base_url, mut requests) = mock_server_with(|request| {
+        let key = match request.contains("key=b") {
+            true => "b",
+            false => "a",
+        };
+        http_response(
+            "200 OK",
+            "application/json",
+            &format!(
+                r#"{{"success":true,"id":"a","data":{{"key":"{key}","valid":true,"active":true}}}}"#
+            ),
+        )
+    })
+    .await;
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .coalesce_requests(true)
+        .build()
+        .unwrap();
+
+    let (first, second) = tokio::join!(
+        client.key_data("a".to_owned()),
+        client.key_data("b".to_owned())
+    );
+    assert_eq!(first.unwrap().key, "a");
+    assert_eq!(second.unwrap().key, "b");
+    assert!(requests.recv().await.is_some());
+    assert!(requests.recv().await.is_some());
+}
+
+#[tokio::test]
+async fn missing_key_test() {
+    let client = Client::builder(" ".to_owned())
+        .base_url("http://127.0.0.1:1".to_owned())
+        .build()
+        .unwrap();
+
+    match client.player_data("uuid".to_owned()).await {
+        Ok(_) => panic!("expected a missing key"),
+        Err(error) => assert_eq!(error[0].r#type, "missing-key"),
+    }
+}
+
+#[tokio::test]
+async fn hypixel_api_test() {
+    let client = Client::with_stub(HashMap::from([(
+        "staff-tracker".to_owned(),
+        r#"{"success":true,"id":"stub","data":[{"uuid":"a","rank":"ADMIN"}]}"#.to_owned(),
+    )]));
+    let api: &dyn HypixelApi = &client;
+
+    assert_eq!(api.staff_tracker("all".to_owned()).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn player_data_by_nickname_test() {
+    let client = |history: &str| {
+        Client::with_stub(HashMap::from([
+            (
+                "nickname-history".to_owned(),
+                format!(r#"{{"success":true,"id":"stub","data":[{history}]}}"#),
+            ),
+            (
+                "player-data".to_owned(),
+                r#"{"success":true,"id":"stub","data":{"uuid":"a","tracker":{}}}"#.to_owned(),
+            ),
+        ]))
+    };
+    let entry = |uuid: &str, active: bool| {
+        format!(
+            r#"{{"uuid":"{uuid}","nickname":"n","active":{active},"created_at":"","voided_at":""}}"#
+        )
+    };
+    let lookup = |history: String| async move {
+        client(&history)
+            .player_data_by_nickname("n".to_owned())
+            .await
+            .map_err(|error| error[0].r#type.clone())
+    };
+
+    let history = [entry("b", false), entry("a", true)].join(",");
+    assert_eq!(lookup(history).await.unwrap().uuid, "a");
+    assert_eq!(
+        lookup(entry("a", false)).await.err().unwrap(),
+        "nickname-not-found"
+    );
+    let history = [entry("a", true), entry("b", true)].join(",");
+    assert_eq!(lookup(history).await.err().unwrap(), "ambiguous-nickname");
+}
+
+#[tokio::test]
+async fn on_request_test() {
+    let (base_url, _) =
+        mock_server(http_response("403 Forbidden", "text/html", "<html></html>")).await;
+    let recorded = Arc::new(Mutex::new(Vec::new()));
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .on_request({
+            let recorded = recorded.clone();
+            move |endpoint, status, _| recorded.lock().unwrap().push((endpoint.to_owned(), status))
+        })
+        .build()
+        .unwrap();
+
+    let _ = client.player_data("uuid".to_owned()).await;
+    assert_eq!(
+        *recorded.lock().unwrap(),
+        [("player-data".to_owned(), Some(403))]
+    );
+}
+
+#[tokio::test]
+async fn empty_results_test() {
+    let client = |body: &str| {
+        Client::with_stub(HashMap::from([(
+            "nickname-history".to_owned(),
+            body.to_owned(),
+        )]))
+    };
+    let history = |body: &'static str| async move {
+        client(body)
+            .nickname_history("never_used".to_owned())
+            .await
+            .map_err(|error| error[0].r#type.clone())
+    };
+
+    assert_eq!(
+        history(r#"{"success":true,"id":"a","data":[]}"#).await,
+        Ok(vec![])
+    );
+    assert_eq!(
+        history(r#"{"success":true,"id":"a","data":null}"#).await,
+        Err("missing-data".to_owned())
+    );
+    assert_eq!(
+        history(r#"{"success":false,"id":"a","errors":[]}"#).await,
+        Err("unexpected-error".to_owned())
+    );
+}
+
+#[tokio::test]
+async fn max_response_bytes_test() {
+    let body = r#"{"success":true,"id":"a","data":[]}"#;
+    let (base_url, _) = mock_server(http_response("200 OK", "application/json", body)).await;
+    let client = |max_bytes| {
+        Client::builder("key".to_owned())
+            .base_url(base_url.clone())
+            .max_response_bytes(max_bytes)
+            .build()
+            .unwrap()
+    };
+
+    assert!(client(body.len())
+        .staff_tracker("all".to_owned())
+        .await
+        .is_ok());
+    match client(body.len() - 1).staff_tracker("all".to_owned()).await {
+        Ok(_) => panic!("expected the response to be too large"),
+        Err(error) => assert_eq!(error[0].r#type, "response-too-large"),
+    }
+}
+
+#[tokio::test]
+async fn borrowed_test() {
+    let client = Client::with_stub(HashMap::from([(
+        "staff-tracker".to_owned(),
+        r#"{"success":true,"id":"stub","data":[{"uuid":"a","rank":"AD\u004dIN","online":1}]}"#
+            .to_owned(),
+    )]));
+
+    let mut buffer = Vec::new();
+    let staff = client
+        .staff_tracker_borrowed("all".to_owned(), &mut buffer)
+        .await
+        .unwrap();
+    assert!(matches!(staff[0].uuid, std::borrow::Cow::Borrowed("a")));
+    assert!(matches!(&staff[0].rank, std::borrow::Cow::Owned(rank) if rank == "ADMIN"));
+    assert_eq!(staff[0].online, Some(true));
+}
+
+#[tokio::test]
+async fn player_data_by_nicknames_test() {
+    let client = Client::with_stub(HashMap::from([
+        (
+            "nickname-history".to_owned(),
+            r#"{"success":true,"id":"stub","data":[{"uuid":"a","nickname":"n","active":true,"created_at":"","voided_at":""}]}"#
+                .to_owned(),
+        ),
+        (
+            "player-data".to_owned(),
+            r#"{"success":true,"id":"stub","data":{"uuid":"a","tracker":{}}}"#.to_owned(),
+        ),
+    ]));
+
+    let players = client
+        .player_data_by_nicknames(vec!["n".to_owned(), "N".to_owned()], 2)
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(players.len(), 2);
+    assert!(players
+        .iter()
+        .all(|player| player.as_ref().unwrap().uuid == "a"));
+}
+
+#[tokio::test]
+async fn error_endpoint_test() {
+    let client = Client::with_stub(HashMap::from([(
+        "player-data".to_owned(),
+        r#"{"success":false,"id":"stub","errors":[{"type":"no-identifier","code":400,"message":"missing uuid"}]}"#
+            .to_owned(),
+    )]));
+
+    let error = client.player_data(String::new()).await.err().unwrap();
+    assert_eq!(error[0].endpoint, "player-data");
+    let error = client.staff_tracker("all".to_owned()).await.err().unwrap();
+    assert_eq!(error[0].endpoint, "staff-tracker");
+}
+
+#[tokio::test]
+async fn etag_cache_test() {
+    let (base_url, mut requests) = mock_server_with(|request| {
+        if request.contains("if-none-match: \"v1\"") {
+            "HTTP/1.1 304 Not Modified\r\netag: \"v1\"\r\nconnection: close\r\n\r\n".to_owned()
+        } else {
+            let body = r#"{"success":true,"id":"a","data":[{"uuid":"a","rank":"ADMIN"}]}"#;
+            format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\netag: \"v1\"\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            )
+        }
+    })
+    .await;
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .local_cache(true)
+        .build()
+        .unwrap();
+
+    let first = client.staff_tracker_cached("all".to_owned()).await.unwrap();
+    let second = client.staff_tracker_cached("all".to_owned()).await.unwrap();
+    assert_eq!(first.data, second.data);
+    assert!(!first.from_cache);
+    assert!(second.from_cache);
+    assert!(!requests.recv().await.unwrap().contains("if-none-match"));
+    assert!(requests
+        .recv()
+        .await
+        .unwrap()
+        .contains("if-none-match: \"v1\""));
+}
+
+#[tokio::test]
+async fn local_cache_keys_test() {
+    let (base_url, mut requests) = mock_server_with(|request| {
+        let key = match request.contains("key=b") {
+            true => "b",
+            false => "a",
+        };
+        let body = format!(
+            r#"{{"success":true,"id":"a","data":{{"key":"{key}","valid":true,"active":true}}}}"#
+        );
+        format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\netag: \"{key}\"\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    })
+    .await;
+    let client = Client::builder("a".to_owned())
+        .base_url(base_url)
+        .local_cache(true)
+        .local_cache_capacity(2)
+        .build()
+        .unwrap();
+
+    assert!(client.can_use("player-data").await.is_ok());
+    client.set_key("b".to_owned());
+    assert_eq!(client.key_data("b".to_owned()).await.unwrap().key, "b");
+    assert!(!requests.recv().await.unwrap().contains("if-none-match"));
+    assert!(!requests.recv().await.unwrap().contains("if-none-match"));
+
+    client
+        .get_value("staff-tracker", &[("filter", "all")])
+        .await
+        .unwrap();
+    let cache = client.local_cache.as_ref().unwrap().lock().unwrap();
+    assert_eq!(cache.len(), 2);
+    assert!(!cache.contains_key("key&key=a"));
+}
+
+#[tokio::test]
+async fn player_data_shared_test() {
+    let (base_url, _) = mock_server_with(|request| {
+        if request.contains("if-none-match: \"v1\"") {
+            "HTTP/1.1 304 Not Modified\r\netag: \"v1\"\r\nconnection: close\r\n\r\n".to_owned()
+        } else {
+            let body = r#"{"success":true,"id":"a","data":{"uuid":"a","tracker":{}}}"#;
+            format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\netag: \"v1\"\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            )
+        }
+    })
+    .await;
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url.clone())
+        .local_cache(true)
+        .build()
+        .unwrap();
+
+    let first = client.player_data_shared("a".to_owned()).await.unwrap();
+    let second = client.player_data_shared("a".to_owned()).await.unwrap();
+    assert!(!first.from_cache);
+    assert!(second.from_cache);
+    assert!(Arc::ptr_eq(&first.data, &second.data));
+    assert_eq!(second.data.uuid, "a");
+
+    let uncached = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .build()
+        .unwrap();
+    let first = uncached.player_data_shared("a".to_owned()).await.unwrap();
+    let second = uncached.player_data_shared("a".to_owned()).await.unwrap();
+    assert!(!Arc::ptr_eq(&first.data, &second.data));
+}
+
+#[tokio::test]
+async fn watch_staff_test() {
+    let client = Client::with_stub(HashMap::from([(
+        "staff-tracker".to_owned(),
+        r#"{"success":true,"id":"stub","data":[{"uuid":"a","rank":"ADMIN","online":true},{"uuid":"b","rank":"MOD","online":false}]}"#
+            .to_owned(),
+    )]));
+
+    let diffs = client
+        .watch_staff(Duration::from_millis(1))
+        .take(2)
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(
+        diffs[0].as_ref().unwrap().went_online,
+        HashSet::from(["a".to_owned()])
+    );
+    assert_eq!(diffs[1].as_ref().unwrap(), &StaffDiff::default());
+}
+
+#[tokio::test]
+async fn request_hook_test() {
+    let (base_url, mut requests) = mock_server(http_response(
+        "200 OK",
+        "application/json",
+        r#"{"success":true,"id":"a","data":[]}"#,
+    ))
+    .await;
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .request_hook(|request| request.header("x-trace-id", "abc"))
+        .build()
+        .unwrap();
+
+    client.staff_tracker("all".to_owned()).await.unwrap();
+    assert!(requests.recv().await.unwrap().contains("x-trace-id: abc"));
+}
+
+#[tokio::test]
+async fn inspect_responses_test() {
+    let (base_url, _) = mock_server(http_response(
+        "200 OK",
+        "application/json",
+        r#"{"success":true,"id":"a","data":[]}"#,
+    ))
+    .await;
+    let inspected = Arc::new(Mutex::new(Vec::new()));
+    let client = Client::builder("secret".to_owned())
+        .base_url(base_url)
+        .inspect_responses({
+            let inspected = inspected.clone();
+            move |url, status, headers, body| {
+                let content_type = headers[reqwest::header::CONTENT_TYPE].to_str().unwrap();
+                inspected
+                    .lock()
+                    .unwrap()
+                    .push(format!("{url} {status} {content_type} {body}"));
+            }
+        })
+        .build()
+        .unwrap();
+
+    client.staff_tracker("all".to_owned()).await.unwrap();
+    let inspected = inspected.lock().unwrap();
+    assert_eq!(inspected.len(), 1);
+    assert!(!inspected[0].contains("secret"));
+    assert!(inspected[0].contains("key=REDACTED"));
+    assert!(inspected[0].ends_with(r#" 200 application/json {"success":true,"id":"a","data":[]}"#));
+}
+
+#[tokio::test]
+async fn deadline_test() {
+    let (base_url, mut requests) = mock_server(http_response(
+        "200 OK",
+        "application/json",
+        r#"{"success":true,"id":"a","data":[]}"#,
+    ))
+    .await;
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .build()
+        .unwrap();
+
+    let expired = client.with_deadline(Instant::now());
+    let errors = expired.staff_tracker("all".to_owned()).await.unwrap_err();
+    assert_eq!(errors[0].r#type, "deadline-exceeded");
+    assert!(requests.try_recv().is_err());
+
+    let pending = client.with_deadline(Instant::now() + Duration::from_secs(30));
+    pending.staff_tracker("all".to_owned()).await.unwrap();
+    assert!(requests.recv().await.is_some());
+    client.staff_tracker("all".to_owned()).await.unwrap();
+}
+
+#[tokio::test]
+async fn queued_deadline_test() {
+    let (base_url, mut requests) = mock_server(http_response(
+        "200 OK",
+        "application/json",
+        r#"{"success":true,"id":"a","data":[]}"#,
+    ))
+    .await;
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .max_concurrent_requests(1)
+        .build()
+        .unwrap();
+
+    let held = client
+        .limiter
+        .as_ref()
+        .unwrap()
+        .acquire(Priority::High)
+        .await;
+    let queued = client.with_deadline(Instant::now() + Duration::from_millis(50));
+    let errors = queued.staff_tracker("all".to_owned()).await.unwrap_err();
+    assert_eq!(errors[0].r#type, "deadline-exceeded");
+    assert!(requests.try_recv().is_err());
+
+    drop(held);
+    client.staff_tracker("all".to_owned()).await.unwrap();
+    assert_eq!(
+        client
+            .limiter
+            .as_ref()
+            .unwrap()
+            .state
+            .lock()
+            .unwrap()
+            .available,
+        1
+    );
+}
+
+#[tokio::test]
+async fn player_data_all_test() {
+    let (base_url, mut requests) = mock_server_with(|request| {
+        let uuid = request.split("uuid=").nth(1).unwrap_or_default();
+        let uuid = &uuid[..uuid.find(' ').unwrap_or_default()];
+        let body = match uuid {
+            "b" => r#"{"success":false,"id":"a","errors":[{"type":"no-identifier","code":400,"message":"missing uuid"}]}"#.to_owned(),
+            _ => format!(r#"{{"success":true,"id":"a","data":{{"uuid":"{uuid}","tracker":{{}}}}}}"#),
+        };
+        http_response("200 OK", "application/json", &body)
+    })
+    .await;
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .build()
+        .unwrap();
+    let uuids = || ["a", "b", "c", "d"].map(str::to_owned);
+
+    let (uuid, errors) = client.player_data_all(uuids(), 1).await.err().unwrap();
+    assert_eq!(uuid, "b");
+    assert_eq!(errors[0].r#type, "no-identifier");
+    assert!(requests.recv().await.unwrap().contains("uuid=a "));
+    assert!(requests.recv().await.unwrap().contains("uuid=b "));
+    assert!(requests.try_recv().is_err());
+
+    let players = client
+        .player_data_all(["a", "c", "d"].map(str::to_owned), 2)
+        .await
+        .unwrap();
+    let uuids: Vec<_> = players.iter().map(|player| player.uuid.as_str()).collect();
+    assert_eq!(uuids, ["a", "c", "d"]);
+}
+
+#[tokio::test]
+async fn player_data_batch_test() {
+    let (base_url, _) = mock_server_with(|request| {
+        let body = match request.contains("uuid=b ") {
+            true => r#"{"success":false,"id":"a","errors":[{"type":"no-identifier","code":400,"message":"missing uuid"}]}"#,
+            false => r#"{"success":true,"id":"a","data":{"uuid":"a","tracker":{}}}"#,
+        };
+        http_response("200 OK", "application/json", body)
+    })
+    .await;
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .build()
+        .unwrap();
+    let uuids = || ["a", "b", "c"].map(str::to_owned);
+
+    let (uuid, _) = client
+        .player_data_batch(uuids(), 1, true)
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(uuid, "b");
+
+    let results = client.player_data_batch(uuids(), 2, false).await.unwrap();
+    let failed: Vec<_> = results
+        .iter()
+        .filter(|(_, result)| result.is_err())
+        .map(|(uuid, _)| uuid.as_str())
+        .collect();
+    assert_eq!(failed, ["b"]);
+    assert_eq!(results.len(), 3);
+}
+
+#[tokio::test]
+async fn key_header_test() {
+    let (base_url, mut requests) = mock_server(http_response(
+        "200 OK",
+        "application/json",
+        r#"{"success":true,"id":"a","data":[]}"#,
+    ))
+    .await;
+    let client = Client::builder("secret".to_owned())
+        .base_url(base_url)
+        .key_header(reqwest::header::HeaderName::from_static("x-api-key"))
+        .build()
+        .unwrap();
+
+    client.staff_tracker("all".to_owned()).await.unwrap();
+    let request = requests.recv().await.unwrap();
+    assert!(request.starts_with("GET /staff-tracker?cache=false&filter=all "));
+    assert!(request.contains("x-api-key: secret"));
+    assert!(!client.build_url("staff-tracker", &[]).contains("key="));
+
+    client.set_key("sec\nret".to_owned());
+    let errors = client.staff_tracker("all".to_owned()).await.unwrap_err();
+    assert_eq!(errors[0].r#type, "invalid-key");
+    assert!(matches!(
+        errors[0].classify(),
+        crate::KnownError::InvalidKey
+    ));
+    assert!(requests.try_recv().is_err());
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn span_name_test() {
+    let query = [("key", "secret"), ("cache", "false"), ("uuid", "a")];
+    let client = Client::new("secret".to_owned(), false);
+    assert_eq!(client.span_name("player-data", &query), "player-data");
+
+    let client = Client::builder("secret".to_owned())
+        .span_name(|endpoint, params| format!("{endpoint} {params:?}"))
+        .build()
+        .unwrap();
+    assert_eq!(
+        client.span_name("player-data", &query),
+        r#"player-data [("cache", "false"), ("uuid", "a")]"#
+    );
+}
+
+#[tokio::test]
+async fn warnings_test() {
+    let client = Client::with_stub(HashMap::from([
+        (
+            "player-data".to_owned(),
+            r#"{"success":true,"id":"stub","data":{"uuid":"a","tracker":{}},"errors":[
+                {"type":"tunnel-blocked","code":503,"message":"ip history unavailable"}
+            ]}"#
+            .to_owned(),
+        ),
+        (
+            "staff-tracker".to_owned(),
+            r#"{"success":true,"id":"stub","data":[]}"#.to_owned(),
+        ),
+    ]));
+
+    let player = client.player_data_cached("a".to_owned()).await.unwrap();
+    assert_eq!(player.data.uuid, "a");
+    assert_eq!(player.warnings.len(), 1);
+    assert_eq!(player.warnings[0].r#type, "tunnel-blocked");
+    assert_eq!(player.warnings[0].request_id.as_deref(), Some("stub"));
+    assert!(!player.warnings[0].internal);
+    assert_eq!(
+        client
+            .player_data_shared("a".to_owned())
+            .await
+            .unwrap()
+            .warnings,
+        player.warnings
+    );
+    assert!(client.player_data("a".to_owned()).await.is_ok());
+
+    let staff = client.staff_tracker_cached("all".to_owned()).await.unwrap();
+    assert!(staff.warnings.is_empty());
+}
+
+#[tokio::test]
+async fn invalidate_player_test() {
+    let (base_url, _) = mock_server_with(|request| {
+        let body = if request.contains("/player-data") {
+            r#"{"success":true,"id":"a","data":{"uuid":"EEA2D4FD-A8B8-413B-9439-F06FAAF7E109","tracker":{}}}"#
+        } else if request.contains("/nickname-history") {
+            r#"{"success":true,"id":"a","data":[{"uuid":"eea2d4fda8b8413b9439f06faaf7e109","nickname":"n","active":true,"created_at":""}]}"#
+        } else {
+            r#"{"success":true,"id":"a","data":[{"uuid":"7d9c4e7bc5d24bb2b4c1c6a1e4bb2e60","rank":"ADMIN"}]}"#
+        };
+        format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\netag: \"v1\"\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    })
+    .await;
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .local_cache(true)
+        .build()
+        .unwrap();
+    assert_eq!(
+        Client::new("key".to_owned(), false).invalidate_player("a"),
+        0
+    );
+
+    client
+        .player_data("eea2d4fd-a8b8-413b-9439-f06faaf7e109".to_owned())
+        .await
+        .unwrap();
+    client.nickname_history("n".to_owned()).await.unwrap();
+    client.staff_tracker("all".to_owned()).await.unwrap();
+    assert_eq!(
+        client.invalidate_player("7d9c4e7b-c5d2-4bb2-b4c1-c6a1e4bb2e60"),
+        1
+    );
+    assert_eq!(
+        client.invalidate_player("EEA2D4FDA8B8413B9439F06FAAF7E109"),
+        2
+    );
+    assert_eq!(
+        client.invalidate_player("eea2d4fda8b8413b9439f06faaf7e109"),
+        0
+    );
+}
+
+#[tokio::test]
+async fn cache_ttl_test() {
+    let (base_url, mut requests) = mock_server_with(|request| {
+        let body = match request.contains("/staff-tracker") {
+            true => r#"{"success":true,"id":"a","data":[]}"#,
+            false => r#"{"success":true,"id":"a","data":{"uuid":"a","tracker":{}}}"#,
+        };
+        http_response("200 OK", "application/json", body)
+    })
+    .await;
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .local_cache(true)
+        .cache_ttl(Duration::from_secs(60))
+        .endpoint_cache_ttl("staff-tracker", Duration::ZERO)
+        .build()
+        .unwrap();
+
+    let first = client.player_data_cached("a".to_owned()).await.unwrap();
+    let second = client.player_data_cached("a".to_owned()).await.unwrap();
+    assert!(!first.from_cache);
+    assert!(second.from_cache);
+    assert_eq!(second.data.uuid, "a");
+    assert!(requests.recv().await.unwrap().contains("/player-data"));
+    assert!(requests.try_recv().is_err());
+
+    client.staff_tracker("all".to_owned()).await.unwrap();
+    let staff = client.staff_tracker_cached("all".to_owned()).await.unwrap();
+    assert!(!staff.from_cache);
+    assert!(requests.recv().await.unwrap().contains("/staff-tracker"));
+    assert!(requests.recv().await.unwrap().contains("/staff-tracker"));
+}
+
+#[tokio::test]
+async fn cache_ttl_error_test() {
+    let attempts = std::sync::atomic::AtomicUsize::new(0);
+    let (base_url, mut requests) = mock_server_with(move |_| {
+        let body = match attempts.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+            0 => r#"{"success":false,"id":"a","errors":[{"type":"internal","code":500,"message":"try again"}]}"#,
+            _ => r#"{"success":true,"id":"a","data":{"uuid":"a","tracker":{}}}"#,
+        };
+        http_response("200 OK", "application/json", body)
+    })
+    .await;
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .local_cache(true)
+        .cache_ttl(Duration::from_secs(60))
+        .build()
+        .unwrap();
+
+    assert!(client.player_data_cached("a".to_owned()).await.is_err());
+    let player = client.player_data_cached("a".to_owned()).await.unwrap();
+    assert!(!player.from_cache);
+    assert_eq!(player.data.uuid, "a");
+    assert!(requests.recv().await.unwrap().contains("/player-data"));
+    assert!(requests.recv().await.unwrap().contains("/player-data"));
+}
+
+#[tokio::test]
+async fn cache_ttl_keys_test() {
+    let (base_url, mut requests) = mock_server_with(|request| {
+        let key = match request.contains("key=b") {
+            true => "b",
+            false => "a",
+        };
+        http_response(
+            "200 OK",
+            "application/json",
+            &format!(
+                r#"{{"success":true,"id":"a","data":{{"key":"{key}","valid":true,"active":true}}}}"#
+            ),
+        )
+    })
+    .await;
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .local_cache(true)
+        .cache_ttl(Duration::from_secs(60))
+        .build()
+        .unwrap();
+
+    assert_eq!(client.key_data("a".to_owned()).await.unwrap().key, "a");
+    assert_eq!(client.key_data("b".to_owned()).await.unwrap().key, "b");
+    assert_eq!(client.key_data("a".to_owned()).await.unwrap().key, "a");
+    assert!(requests.recv().await.unwrap().contains("key=a"));
+    assert!(requests.recv().await.unwrap().contains("key=b"));
+    assert!(requests.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn current_nickname_test() {
+    let client = Client::with_stub(HashMap::from([(
+        "player-data".to_owned(),
+        r#"{"success":true,"id":"stub","data":{"uuid":"a","tracker":{},"nickname_history":[
+            {"nickname":"caykey","active":true,"created_at":"2022-01-01T00:00:00Z"}
+        ]}}"#
+            .to_owned(),
+    )]));
+    assert_eq!(
+        client
+            .current_nickname("a".to_owned())
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("caykey")
+    );
+
+    let client = Client::with_stub(HashMap::from([(
+        "player-data".to_owned(),
+        r#"{"success":true,"id":"stub","data":{"uuid":"a","tracker":{}}}"#.to_owned(),
+    )]));
+    assert_eq!(client.current_nickname("a".to_owned()).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn circuit_breaker_test() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    let healthy = Arc::new(AtomicBool::new(false));
+    let (base_url, mut requests) = mock_server_with({
+        let healthy = healthy.clone();
+        move |_| match healthy.load(Ordering::SeqCst) {
+            true => http_response(
+                "200 OK",
+                "application/json",
+                r#"{"success":true,"id":"a","data":[]}"#,
+            ),
+            false => http_response("502 Bad Gateway", "application/json", "{}"),
+        }
+    })
+    .await;
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .circuit_breaker(2, Duration::from_secs(60), Duration::from_millis(200))
+        .build()
+        .unwrap();
+    let staff = || client.staff_tracker("all".to_owned());
+
+    assert_ne!(staff().await.unwrap_err()[0].r#type, "circuit-open");
+    assert_ne!(staff().await.unwrap_err()[0].r#type, "circuit-open");
+    assert_eq!(staff().await.unwrap_err()[0].r#type, "circuit-open");
+    assert!(requests.recv().await.is_some());
+    assert!(requests.recv().await.is_some());
+    assert!(requests.try_recv().is_err());
+
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert_ne!(staff().await.unwrap_err()[0].r#type, "circuit-open");
+    assert_eq!(staff().await.unwrap_err()[0].r#type, "circuit-open");
+
+    healthy.store(true, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    staff().await.unwrap();
+    staff().await.unwrap();
+}
+
+#[tokio::test]
+async fn priority_test() {
+    let limiter = Arc::new(Limiter {
+        state: Mutex::new(LimiterState {
+            available: 1,
+            waiting: Default::default(),
+        }),
+    });
+    let held = limiter.acquire(Priority::Normal).await;
+
+    let (order, mut acquired) = tokio::sync::mpsc::unbounded_channel();
+    let mut waiting = Vec::new();
+    for priority in [
+        Priority::Low,
+        Priority::Normal,
+        Priority::High,
+        Priority::High,
+    ] {
+        let (limiter, order) = (limiter.clone(), order.clone());
+        waiting.push(tokio::spawn(async move {
+            let _permit = limiter.acquire(priority).await;
+            order.send(priority).unwrap();
+        }));
+        tokio::task::yield_now().await;
+    }
+    let cancelled = tokio::spawn({
+        let limiter = limiter.clone();
+        async move { limiter.acquire(Priority::High).await }
+    });
+    tokio::task::yield_now().await;
+    cancelled.abort();
+    let _ = cancelled.await;
+
+    drop(held);
+    for task in waiting {
+        task.await.unwrap();
+    }
+    let mut order_received = Vec::new();
+    while let Ok(priority) = acquired.try_recv() {
+        order_received.push(priority);
+    }
+    assert_eq!(
+        order_received,
+        [
+            Priority::High,
+            Priority::High,
+            Priority::Normal,
+            Priority::Low
+        ]
+    );
+    assert_eq!(limiter.state.lock().unwrap().available, 1);
+
+    let (base_url, _) = mock_server(http_response(
+        "200 OK",
+        "application/json",
+        r#"{"success":true,"id":"a","data":[]}"#,
+    ))
+    .await;
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .max_concurrent_requests(1)
+        .build()
+        .unwrap()
+        .with_priority(Priority::High);
+    let background = client.with_priority(Priority::Low);
+    let (first, second) = tokio::join!(
+        client.staff_tracker("all".to_owned()),
+        background.staff_tracker("all".to_owned())
+    );
+    assert!(first.is_ok() && second.is_ok());
+}
+
+#[tokio::test]
+async fn punishments_for_player_test() {
+    let client = Client::with_stub(HashMap::from([
+        (
+            "player-data".to_owned(),
+            r#"{"success":true,"id":"stub","data":{"uuid":"a","tracker":{},"infractions":[
+                {"id":"C256D602","punishment_type":"BAN","reason":"cheating"},
+                {"id":"C256D603","punishment_type":"MUTE","reason":"spam"}
+            ]}}"#
+                .to_owned(),
+        ),
+        (
+            "punishment-data".to_owned(),
+            r#"{"success":true,"id":"stub","data":{"id":"C256D602","punishment_type":"BAN","uuid":"a","reason":"cheating"}}"#
+                .to_owned(),
+        ),
+    ]));
+
+    let player = client.player_data("a".to_owned()).await.unwrap();
+    assert_eq!(player.punishment_ids(), ["C256D602", "C256D603"]);
+
+    let punishments = client.punishments_for_player("a".to_owned()).await.unwrap();
+    assert_eq!(punishments.len(), 2);
+    assert!(punishments.iter().all(|punishment| punishment.is_ok()));
+}
+
+#[tokio::test]
+async fn staff_tracker_sorted_test() {
+    let client = Client::with_stub(HashMap::from([(
+        "staff-tracker".to_owned(),
+        r#"{"success":true,"id":"stub","data":[
+            {"uuid":"b","rank":"MOD","online":true},
+            {"uuid":"c","rank":"ADMIN","online":false},
+            {"uuid":"d","rank":"BUILD_TEAM"},
+            {"uuid":"a","rank":"MOD","online":false},
+            {"uuid":"e","rank":"OWNER"},
+            {"uuid":"a","rank":"ADMIN"}
+        ]}"#
+        .to_owned(),
+    )]));
+
+    let order = |staff: Vec<StaffTracker>| {
+        staff
+            .into_iter()
+            .map(|staff| format!("{}:{}", staff.rank, staff.uuid))
+            .collect::<Vec<_>>()
+    };
+    let unsorted = client.staff_tracker("all".to_owned()).await.unwrap();
+    assert_eq!(
+        order(unsorted),
+        [
+            "MOD:b",
+            "ADMIN:c",
+            "BUILD_TEAM:d",
+            "MOD:a",
+            "OWNER:e",
+            "ADMIN:a"
+        ]
+    );
+    let sorted = client.staff_tracker_sorted("all".to_owned()).await.unwrap();
+    assert_eq!(
+        order(sorted),
+        [
+            "OWNER:e",
+            "ADMIN:a",
+            "ADMIN:c",
+            "MOD:a",
+            "MOD:b",
+            "BUILD_TEAM:d"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn init_test() {
+    let client = Client::with_stub(HashMap::from([
+        (
+            "key".to_owned(),
+            r#"{"success":true,"id":"stub","data":{"key":"stub","valid":true,"active":true,"endpoints":[{"id":"player-data","status":true,"version":3},{"id":"staff-tracker","status":false,"version":3}]}}"#.to_owned(),
+        ),
+        (
+            "player-data".to_owned(),
+            r#"{"success":true,"id":"stub","data":{"uuid":"a","tracker":{}}}"#.to_owned(),
+        ),
+        (
+            "staff-tracker".to_owned(),
+            r#"{"success":true,"id":"stub","data":[]}"#.to_owned(),
+        ),
+    ]));
+    let clone = client.clone();
+
+    client.staff_tracker("all".to_owned()).await.unwrap();
+    client.init().await.unwrap();
+    client.player_data("a".to_owned()).await.unwrap();
+    client.key_data("other".to_owned()).await.unwrap();
+    for endpoint in ["staff-tracker", "nickname-history"] {
+        let error = clone.get_value(endpoint, &[]).await.unwrap_err();
+        assert_eq!(error[0].r#type, "disabled-endpoint");
+        assert_eq!(error[0].endpoint, endpoint);
+    }
+
+    client.set_key("new".to_owned());
+    client.staff_tracker("all".to_owned()).await.unwrap();
+}
+
+#[tokio::test]
+async fn connect_test() {
+    let key = |active| {
+        Client::with_stub(HashMap::from([(
+            "key".to_owned(),
+            format!(
+                r#"{{"success":true,"id":"stub","data":{{"key":"stub","valid":true,"active":{active},"endpoints":[{{"id":"player-data","status":true,"version":3}}]}}}}"#
+            ),
+        )]))
+    };
+
+    let client = key(true).validated().await.unwrap();
+    assert!(client.key_data.lock().unwrap().is_some());
+
+    match key(false).validated().await {
+        Ok(_) => panic!("expected a rejected key"),
+        Err(error) => {
+            assert_eq!(error[0].r#type, "rejected-key");
+            assert!(matches!(error[0].classify(), crate::KnownError::InvalidKey));
+        }
+    }
+}
+
+#[tokio::test]
+async fn player_data_many_test() {
+    let (base_url, _) = mock_server_with(|request| match request.contains("uuid=a") {
+        true => http_response(
+            "200 OK",
+            "application/json",
+            r#"{"success":true,"id":"a","data":{"uuid":"a","tracker":{}}}"#,
+        ),
+        false => http_response(
+            "200 OK",
+            "application/json",
+            r#"{"success":false,"id":"b","errors":[{"type":"no-identifier","code":400,"message":"invalid uuid"}]}"#,
+        ),
+    })
+    .await;
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .build()
+        .unwrap();
+
+    let results = client
+        .player_data_many(["a".to_owned(), "invalid".to_owned()], 2)
+        .await;
+    let (players, failures) = crate::split_results(results);
+    assert_eq!(players.len(), 1);
+    assert_eq!(players[0].uuid, "a");
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].0, "invalid");
+    assert_eq!(failures[0].1[0].r#type, "no-identifier");
+}
+
+#[tokio::test]
+async fn invalid_punishment_id_test() {
+    let client = Client::with_stub(HashMap::new());
+
+    let error = client
+        .punishment_data("C256-602".to_owned())
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(error[0].r#type, "invalid-punishment-id");
+    assert_eq!(error[0].endpoint, "punishment-data");
+}
+
+#[tokio::test]
+async fn set_key_test() {
+    let (base_url, mut requests) = mock_server(http_response(
+        "200 OK",
+        "application/json",
+        r#"{"success":true,"id":"a","data":[]}"#,
+    ))
+    .await;
+    let client = Client::builder("old".to_owned())
+        .base_url(base_url)
+        .build()
+        .unwrap();
+    let clone = client.clone();
+
+    client.staff_tracker("all".to_owned()).await.unwrap();
+    assert!(requests.recv().await.unwrap().contains("key=old"));
+    client.set_key("new".to_owned());
+    clone.staff_tracker("all".to_owned()).await.unwrap();
+    assert!(requests.recv().await.unwrap().contains("key=new"));
+}
+
+#[tokio::test]
+async fn set_key_cache_test() {
+    let (base_url, mut requests) = mock_server(http_response(
+        "200 OK",
+        "application/json",
+        r#"{"success":true,"id":"a","data":[]}"#,
+    ))
+    .await;
+    let client = Client::builder("old".to_owned())
+        .base_url(base_url)
+        .local_cache(true)
+        .cache_ttl(Duration::from_secs(60))
+        .build()
+        .unwrap();
+
+    client.staff_tracker("all".to_owned()).await.unwrap();
+    assert!(requests.recv().await.unwrap().contains("key=old"));
+    client.set_key("new".to_owned());
+    let staff = client.staff_tracker_cached("all".to_owned()).await.unwrap();
+    assert!(!staff.from_cache);
+    assert!(requests.recv().await.unwrap().contains("key=new"));
+}
+
+#[cfg(test)]
+async fn network_error(base_url: String) -> String {
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .request_hook(|request| request.timeout(Duration::from_millis(200)))
+        .build()
+        .unwrap();
+    client.staff_tracker("all".to_owned()).await.err().unwrap()[0]
+        .r#type
+        .clone()
+}
+
+#[tokio::test]
+async fn network_errors_test() {
+    let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", closed.local_addr().unwrap());
+    drop(closed);
+    assert_eq!(network_error(base_url).await, "connect-error");
+
+    let (base_url, _) = mock_server(http_response("400 Bad Request", "text/plain", "")).await;
+    let base_url = base_url.replace("http://", "https://");
+    assert_eq!(network_error(base_url).await, "tls-error");
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        let _socket = listener.accept().await;
+        std::future::pending::<()>().await
+    });
+    assert_eq!(network_error(base_url).await, "timeout");
+    server.abort();
+}
+
+#[tokio::test]
+#[ignore = "depends on the system resolver failing quickly for reserved names"]
+async fn dns_error_test() {
+    assert_eq!(
+        network_error("http://nonexistent.invalid".to_owned()).await,
+        "dns-error"
+    );
+}
+
+#[tokio::test]
+async fn incomplete_response_test() {
+    let body = r#"{"success":true,"id":"a","data":[{"uuid":"a","#;
+    let truncated = [
+        format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: 100\r\nconnection: close\r\n\r\n{body}"
+        ),
+        format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ntransfer-encoding: chunked\r\nconnection: close\r\n\r\n{:x}\r\n{body}\r\n",
+            body.len()
+        ),
+    ];
+    for response in truncated {
+        let (base_url, _) = mock_server(response).await;
+        for max_response_bytes in [None, Some(1024)] {
+            let mut builder = Client::builder("key".to_owned()).base_url(base_url.clone());
+            if let Some(max_bytes) = max_response_bytes {
+                builder = builder.max_response_bytes(max_bytes);
+            }
+            let errors = builder
+                .build()
+                .unwrap()
+                .staff_tracker("all".to_owned())
+                .await
+                .unwrap_err();
+            assert_eq!(errors[0].r#type, "incomplete-response");
+            assert!(errors[0].internal);
+        }
+    }
+}
+
+#[tokio::test]
+async fn normalize_nicknames_test() {
+    let (base_url, mut requests) = mock_server(http_response(
+        "200 OK",
+        "application/json",
+        r#"{"success":true,"id":"a","data":[]}"#,
+    ))
+    .await;
+    let client = |normalize| {
+        Client::builder("key".to_owned())
+            .base_url(base_url.clone())
+            .normalize_nicknames(normalize)
+            .build()
+            .unwrap()
+    };
+
+    client(false)
+        .nickname_history(" CayKey".to_owned())
+        .await
+        .unwrap();
+    assert!(requests.recv().await.unwrap().contains("nickname=+CayKey "));
+    client(true)
+        .nickname_history(" CayKey".to_owned())
+        .await
+        .unwrap();
+    assert!(requests.recv().await.unwrap().contains("nickname=caykey "));
+}
+
+#[test]
+fn from_env_test() {
+    let client = |vars: &[(&str, &str)]| {
+        Client::from_vars(|name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.to_string())
+        })
+    };
+
+    let env = client(&[("CACTIVE_HYPIXEL_API_KEY", "key")]).unwrap();
+    assert_eq!(env.key(), "key");
+    assert!(!env.cache);
+    let env = client(&[
+        ("CACTIVE_HYPIXEL_API_KEY", "key"),
+        ("CACTIVE_HYPIXEL_CACHE", "1"),
+    ])
+    .unwrap();
+    assert!(env.cache);
+
+    let error = client(&[]).err().unwrap();
+    assert_eq!(error[0].r#type, "missing-key");
+    let error = client(&[
+        ("CACTIVE_HYPIXEL_API_KEY", "key"),
+        ("CACTIVE_HYPIXEL_CACHE", "yes"),
+    ])
+    .err()
+    .unwrap();
+    assert_eq!(error[0].r#type, "invalid-environment");
+}
+
+#[tokio::test]
+async fn rate_limit_test() {
+    let (base_url, _) = mock_server(
+        http_response(
+            "200 OK",
+            "application/json",
+            r#"{"success":true,"id":"a","data":[]}"#,
+        )
+        .replacen(
+            "\r\n",
+            "\r\nx-ratelimit-limit: 10\r\nx-ratelimit-remaining: 7\r\nx-ratelimit-reset: 42\r\n",
+            1,
+        ),
+    )
+    .await;
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .build()
+        .unwrap();
+
+    let staff = client.staff_tracker_cached("all".to_owned()).await.unwrap();
+    assert_eq!(
+        staff.rate_limit,
+        Some(RateLimit {
+            limit: Some(10),
+            remaining: Some(7),
+            reset: Some(Duration::from_secs(42)),
+        })
+    );
+}
+
+#[tokio::test]
+async fn with_headers_test() {
+    let (base_url, _) = mock_server(
+        http_response(
+            "200 OK",
+            "application/json",
+            r#"{"success":true,"id":"a","data":[]}"#,
+        )
+        .replacen(
+            "\r\n",
+            "\r\netag: \"v1\"\r\nx-ratelimit-remaining: 7\r\n",
+            1,
+        ),
+    )
+    .await;
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .local_cache(true)
+        .cache_ttl(Duration::from_secs(60))
+        .build()
+        .unwrap();
+
+    let (staff, headers) = client
+        .staff_tracker_with_headers("all".to_owned())
+        .await
+        .unwrap();
+    assert!(staff.is_empty());
+    assert_eq!(headers["etag"], "\"v1\"");
+    assert_eq!(headers["x-ratelimit-remaining"], "7");
+    assert_eq!(headers["content-type"], "application/json");
+
+    let (_, headers) = client
+        .staff_tracker_with_headers("all".to_owned())
+        .await
+        .unwrap();
+    assert!(headers.is_empty());
+}
+
+#[tokio::test]
+async fn cancelled_cache_write_test() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let client = Client::builder("key".to_owned())
+        .base_url(format!("http://{}", listener.local_addr().unwrap()))
+        .local_cache(true)
+        .build()
+        .unwrap();
+
+    let (sender, mut requests) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let sender = sender.clone();
+            tokio::spawn(async move {
+                let mut buffer = [0; 4096];
+                let length = socket.read(&mut buffer).await.unwrap_or(0);
+                let _ = sender.send(String::from_utf8_lossy(&buffer[..length]).into_owned());
+                let body = r#"{"success":true,"id":"a","data":[]}"#;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\netag: \"v1\"\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let (head, tail) = response.split_at(response.len() - 8);
+                let _ = socket.write_all(head.as_bytes()).await;
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                let _ = socket.write_all(tail.as_bytes()).await;
+            });
+        }
+    });
+
+    // Dropped after the first poll, then while the body is still arriving.
+    for timeout in [Duration::ZERO, Duration::from_millis(100)] {
+        let request = client.staff_tracker_cached("all".to_owned());
+        assert!(tokio::time::timeout(timeout, request).await.is_err());
+        assert!(client
+            .local_cache
+            .as_ref()
+            .unwrap()
+            .lock()
+            .unwrap()
+            .is_empty());
+    }
+
+    client.staff_tracker_cached("all".to_owned()).await.unwrap();
+    client.staff_tracker_cached("all".to_owned()).await.unwrap();
+    let requests = std::iter::from_fn(|| requests.try_recv().ok()).collect::<Vec<_>>();
+    let (last, earlier) = requests.split_last().unwrap();
+    assert!(earlier
+        .iter()
+        .all(|request| !request.contains("if-none-match")));
+    assert!(last.contains("if-none-match: \"v1\""));
+}
+
+#[tokio::test]
+async fn trust_status_code_test() {
+    let (base_url, _) = mock_server(http_response(
+        "500 Internal Server Error",
+        "application/json",
+        r#"{"success":true,"id":"a","data":[]}"#,
+    ))
+    .await;
+    let client = |trust| {
+        Client::builder("key".to_owned())
+            .base_url(base_url.clone())
+            .trust_status_code(trust)
+            .build()
+            .unwrap()
+    };
+
+    assert!(client(false).staff_tracker("all".to_owned()).await.is_ok());
+    let error = client(true)
+        .staff_tracker("all".to_owned())
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(error[0].r#type, "unexpected-status");
+    assert_eq!(error[0].code, 500);
+}
+
+#[tokio::test]
+async fn endpoint_timeout_test() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        let mut sockets = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            sockets.push(socket);
+        }
+    });
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .timeout(Duration::from_secs(60))
+        .endpoint_timeout("staff-tracker", Duration::from_millis(100))
+        .build()
+        .unwrap();
+
+    let error = client.staff_tracker("all".to_owned()).await.err().unwrap();
+    assert_eq!(error[0].r#type, "timeout");
+    let pending = tokio::time::timeout(
+        Duration::from_millis(300),
+        client.player_data("a".to_owned()),
+    )
+    .await;
+    assert!(pending.is_err());
+    server.abort();
+}
+
+#[tokio::test]
+async fn prefetch_test() {
+    let (base_url, _) = mock_server_with(|request| {
+        if request.contains("uuid=invalid") {
+            http_response(
+                "200 OK",
+                "application/json",
+                r#"{"success":false,"id":"a","errors":[{"type":"no-identifier","code":400,"message":"invalid uuid"}]}"#,
+            )
+        } else if request.contains("if-none-match: \"v1\"") {
+            "HTTP/1.1 304 Not Modified\r\netag: \"v1\"\r\nconnection: close\r\n\r\n".to_owned()
+        } else {
+            http_response(
+                "200 OK",
+                "application/json",
+                r#"{"success":true,"id":"a","data":{"uuid":"a","tracker":{}}}"#,
+            )
+            .replacen("\r\n", "\r\netag: \"v1\"\r\n", 1)
+        }
+    })
+    .await;
+    let client = Client::builder("key".to_owned())
+        .base_url(base_url)
+        .local_cache(true)
+        .build()
+        .unwrap();
+
+    let summary = client
+        .prefetch(vec!["a".to_owned(), "b".to_owned(), "invalid".to_owned()])
+        .await;
+    assert_eq!(
+        summary,
+        PrefetchSummary {
+            succeeded: 2,
+            failed: 1
+        }
+    );
+    assert!(
+        client
+            .player_data_cached("a".to_owned())
+            .await
+            .unwrap()
+            .from_cache
+    );
+}
@@ -0,0 +1,376 @@
+use std::fmt;
+
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct InternalError {
+    pub r#type: String,
+    pub code: u16,
+    pub message: String,
+    pub internal: bool,
+    #[serde(default)]
+    pub request_id: Option<String>,
+    #[serde(default)]
+    pub endpoint: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct APIError {
+    pub r#type: String,
+    pub code: u16,
+    pub message: String,
+}
+
+/// A classification of an error, see [`InternalError::classify`].
+///
+/// | Variant | Error types | Fallback codes |
+/// | --- | --- | --- |
+/// | `InvalidKey` | `no-authentication`, `invalid-authentication`, `invalid-key`, `missing-key`, `rejected-key` | 401, 403 |
+/// | `RateLimited` | `rate-limit-blocked` | 429 |
+/// | `NotFound` | `nickname-not-found` | 404 |
+/// | `Validation` | `no-identifier`, `no-filter`, `invalid-filter`, `invalid-endpoint`, `invalid-punishment-id` | 400, 422 |
+/// | `Internal` | `tunnel-blocked`, `hypixel-maintenance`, `unexpected-error` | 500-599 |
+/// | `Unknown` | anything else, retaining the original error | |
+#[derive(Debug, Clone)]
+pub enum KnownError {
+    InvalidKey,
+    RateLimited,
+    NotFound,
+    Validation,
+    Internal,
+    Unknown(InternalError),
+}
+
+impl fmt::Display for InternalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}: {}", self.code, self.r#type, self.message)
+    }
+}
+
+impl std::error::Error for InternalError {}
+
+impl InternalError {
+    /// Create an error without a request id or endpoint, such as to fabricate errors when testing error handling.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cactive_hypixel_api::InternalError;
+    ///
+    /// let error = InternalError::new("rate-limit-blocked", 429, "slow down", false);
+    /// ```
+    pub fn new(
+        r#type: impl Into<String>,
+        code: u16,
+        message: impl Into<String>,
+        internal: bool,
+    ) -> Self {
+        InternalError {
+            r#type: r#type.into(),
+            code,
+            message: message.into(),
+            internal,
+            request_id: None,
+            endpoint: String::new(),
+        }
+    }
+
+    /// Create an error as the API would return it, with `internal` set to `false`.
+    pub fn api(r#type: impl Into<String>, code: u16, message: impl Into<String>) -> Self {
+        InternalError::new(r#type, code, message, false)
+    }
+
+    /// Create an error as this client would raise it, with `internal` set to `true`.
+    pub fn internal(r#type: impl Into<String>, code: u16, message: impl Into<String>) -> Self {
+        InternalError::new(r#type, code, message, true)
+    }
+
+    /// Classify the error by its type, falling back to its code, so it can be matched without comparing strings.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cactive_hypixel_api::KnownError;
+    ///
+    /// match client.player_data(uuid).await {
+    ///     Ok(data) => println!("{}", data.uuid),
+    ///     Err(err) => match err[0].classify() {
+    ///         KnownError::RateLimited => println!("Slow down"),
+    ///         _ => println!("{}", err[0]),
+    ///     },
+    /// }
+    /// ```
+    pub fn classify(&self) -> KnownError {
+        match (self.r#type.as_str(), self.code) {
+            (
+                "no-authentication"
+                | "invalid-authentication"
+                | "invalid-key"
+                | "missing-key"
+                | "rejected-key",
+                _,
+            )
+            | (_, 401 | 403) => KnownError::InvalidKey,
+            ("rate-limit-blocked", _) | (_, 429) => KnownError::RateLimited,
+            ("nickname-not-found", _) | (_, 404) => KnownError::NotFound,
+            (
+                "no-identifier"
+                | "no-filter"
+                | "invalid-filter"
+                | "invalid-endpoint"
+                | "invalid-punishment-id",
+                _,
+            )
+            | (_, 400 | 422) => KnownError::Validation,
+            ("tunnel-blocked" | "hypixel-maintenance" | "unexpected-error", _) | (_, 500..=599) => {
+                KnownError::Internal
+            }
+            _ => KnownError::Unknown(self.clone()),
+        }
+    }
+}
+
+impl APIError {
+    /// Classify the error as in [`InternalError::classify`].
+    pub fn classify(&self) -> KnownError {
+        InternalError::from(self.clone()).classify()
+    }
+}
+
+impl fmt::Display for APIError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}: {}", self.code, self.r#type, self.message)
+    }
+}
+
+impl From<APIError> for InternalError {
+    fn from(error: APIError) -> Self {
+        InternalError::api(error.r#type, error.code, error.message)
+    }
+}
+
+impl From<serde_json::Error> for InternalError {
+    fn from(error: serde_json::Error) -> Self {
+        InternalError::internal("failed-api-request", 500, error.to_string())
+    }
+}
+
+/// A vector of errors usable as a standard error, for propagating with `?` into `Box<dyn std::error::Error>`.
+///
+/// # Examples
+///
+/// ```rust
+/// use cactive_hypixel_api::Errors;
+///
+/// let data = client.player_data(uuid).await.map_err(Errors)?;
+/// ```
+#[derive(Debug)]
+pub struct Errors(pub Vec<InternalError>);
+
+impl fmt::Display for Errors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&summarize_errors(&self.0))
+    }
+}
+
+impl std::error::Error for Errors {}
+
+impl From<Vec<InternalError>> for Errors {
+    fn from(errors: Vec<InternalError>) -> Self {
+        Errors(errors)
+    }
+}
+
+/// Split the per-item results of a batch into the successful values and the failed items with their errors, each
+/// in their original order.
+#[cfg_attr(
+    feature = "client",
+    doc = "",
+    doc = "See [`Client::player_data_many`](crate::Client::player_data_many)."
+)]
+///
+/// # Examples
+///
+/// ```rust
+/// use cactive_hypixel_api::split_results;
+///
+/// let (players, failures) = split_results(client.player_data_many(uuids, 4).await);
+/// ```
+pub fn split_results<K, T>(
+    results: Vec<(K, ApiResult<T>)>,
+) -> (Vec<T>, Vec<(K, Vec<InternalError>)>) {
+    let mut successes = Vec::new();
+    let mut failures = Vec::new();
+    for (item, result) in results {
+        match result {
+            Ok(value) => successes.push(value),
+            Err(errors) => failures.push((item, errors)),
+        }
+    }
+    (successes, failures)
+}
+
+/// The result of a request, failing with every error the API or client reported.
+pub type ApiResult<T> = Result<T, Vec<InternalError>>;
+
+/// Shorthands for handling an [`ApiResult`] without indexing into its errors.
+///
+/// # Examples
+///
+/// ```rust
+/// use cactive_hypixel_api::ApiResultExt;
+///
+/// let data = client.player_data(uuid).await.log_errors().first_err()?;
+/// ```
+pub trait ApiResultExt<T> {
+    /// Emit the errors, if any, as a single warning through `tracing` or `log` when either feature is enabled,
+    /// and return the result unchanged.
+    fn log_errors(self) -> Self;
+
+    /// Keep only the first error, or an `unexpected-error` if the list was empty.
+    fn first_err(self) -> Result<T, InternalError>;
+}
+
+impl<T> ApiResultExt<T> for ApiResult<T> {
+    fn log_errors(self) -> Self {
+        if let Err(errors) = &self {
+            log_warn!("{}", summarize_errors(errors));
+        }
+        self
+    }
+
+    fn first_err(self) -> Result<T, InternalError> {
+        self.map_err(|errors| {
+            errors.into_iter().next().unwrap_or_else(|| {
+                InternalError::internal("unexpected-error", 500, "no errors were reported")
+            })
+        })
+    }
+}
+
+/// Join a vector of errors into a single line, including the request id when one was returned.
+///
+/// # Examples
+///
+/// ```rust
+/// use cactive_hypixel_api::summarize_errors;
+///
+/// let data = match client.player_data(uuid).await {
+///     Ok(data) => data,
+///     Err(err) => return println!("{}", summarize_errors(&err)),
+/// };
+/// ```
+pub fn summarize_errors(errors: &[InternalError]) -> String {
+    if errors.is_empty() {
+        return "no errors".to_owned();
+    }
+
+    let summary = errors
+        .iter()
+        .map(|error| error.to_string())
+        .collect::<Vec<_>>()
+        .join("; ");
+
+    match errors.iter().find_map(|error| error.request_id.as_deref()) {
+        Some(id) => format!("request {id}: {summary}"),
+        None => summary,
+    }
+}
+
+#[test]
+fn summarize_errors_test() {
+    let error = |code, message: &str, request_id: Option<&str>| InternalError {
+        request_id: request_id.map(str::to_owned),
+        ..InternalError::api("tunnel-blocked", code, message)
+    };
+
+    assert_eq!(summarize_errors(&[]), "no errors");
+    assert_eq!(
+        summarize_errors(&[error(503, "blocked", None)]),
+        "[503] tunnel-blocked: blocked"
+    );
+    assert_eq!(
+        summarize_errors(&[error(503, "a", Some("abc")), error(500, "b", Some("abc"))]),
+        "request abc: [503] tunnel-blocked: a; [500] tunnel-blocked: b"
+    );
+}
+
+#[test]
+fn boxed_errors_test() {
+    fn fails() -> Result<(), Box<dyn std::error::Error>> {
+        let result: ApiResult<()> = Err(vec![InternalError::api(
+            "no-identifier",
+            400,
+            "missing uuid",
+        )]);
+        result.map_err(Errors)?;
+        Ok(())
+    }
+
+    assert_eq!(
+        fails().unwrap_err().to_string(),
+        "[400] no-identifier: missing uuid"
+    );
+}
+
+#[test]
+fn classify_test() {
+    let error = |r#type: &str, code| InternalError::api(r#type, code, "");
+
+    assert!(matches!(
+        error("invalid-authentication", 200).classify(),
+        KnownError::InvalidKey
+    ));
+    assert!(matches!(
+        error("rate-limit-blocked", 200).classify(),
+        KnownError::RateLimited
+    ));
+    assert!(matches!(
+        error("no-filter", 200).classify(),
+        KnownError::Validation
+    ));
+    assert!(matches!(
+        error("hypixel-maintenance", 200).classify(),
+        KnownError::Internal
+    ));
+    assert!(matches!(
+        error("something-new", 404).classify(),
+        KnownError::NotFound
+    ));
+    assert!(matches!(
+        error("something-new", 418).classify(),
+        KnownError::Unknown(original) if original.r#type == "something-new"
+    ));
+}
+
+#[test]
+fn constructors_test() {
+    let error = InternalError::new("no-identifier", 400, "missing uuid", false);
+    assert_eq!(error.r#type, "no-identifier");
+    assert_eq!(error.code, 400);
+    assert_eq!(error.message, "missing uuid");
+    assert!(!error.internal);
+    assert_eq!(error.request_id, None);
+    assert_eq!(error.endpoint, "");
+
+    assert!(!InternalError::api("tunnel-blocked", 503, "blocked").internal);
+    let error = InternalError::internal("timeout".to_owned(), 504, format!("took {}s", 5));
+    assert!(error.internal);
+    assert_eq!(error.to_string(), "[504] timeout: took 5s");
+}
+
+#[test]
+fn api_result_ext_test() {
+    let ok: ApiResult<u8> = Ok(1);
+    assert_eq!(ok.log_errors().first_err().unwrap(), 1);
+
+    let failed: ApiResult<u8> = Err(vec![
+        InternalError::api("tunnel-blocked", 503, "blocked"),
+        InternalError::api("hypixel-maintenance", 503, "offline"),
+    ]);
+    let error = failed.log_errors().first_err().unwrap_err();
+    assert_eq!(error.r#type, "tunnel-blocked");
+
+    let empty: ApiResult<u8> = Err(Vec::new());
+    assert_eq!(empty.first_err().unwrap_err().r#type, "unexpected-error");
+}
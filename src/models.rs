@@ -0,0 +1,2056 @@
+use std::{
+    borrow::Cow,
+    cmp::{Ordering, Reverse},
+    collections::{BTreeMap, HashSet},
+    convert::Infallible,
+    fmt,
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
+
+use serde::{de, Deserialize, Deserializer};
+
+use crate::{APIError, InternalError, API_VERSION};
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NicknameHistory {
+    pub uuid: String,
+    pub nickname: String,
+    #[serde(deserialize_with = "tolerant_bool")]
+    pub active: bool,
+    pub created_at: String,
+    /// When the entry was voided, `None` while it hasn't been, including when the API sends an empty string.
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub voided_at: Option<String>,
+    /// Fields this crate doesn't model yet, captured with the `capture-unknown` feature.
+    #[cfg(feature = "capture-unknown")]
+    #[serde(flatten, deserialize_with = "unknown_fields::<NicknameHistory, _>")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+pub struct PunishmentData {
+    pub id: String,
+    pub punishment_type: String,
+    pub uuid: String,
+    #[serde(default)]
+    pub executor: Option<String>,
+    pub reason: String,
+    #[serde(default)]
+    pub length: Option<u32>,
+    /// Fields this crate doesn't model yet, captured with the `capture-unknown` feature.
+    #[cfg(feature = "capture-unknown")]
+    #[serde(flatten, deserialize_with = "unknown_fields::<PunishmentData, _>")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PlayerDataNicknameHistory {
+    pub nickname: String,
+    #[serde(default)]
+    pub active: Option<bool>,
+    pub created_at: String,
+    #[serde(default)]
+    pub voided_at: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PlayerDataInfractions {
+    pub id: String,
+    pub punishment_type: String,
+    #[serde(default)]
+    pub executor: Option<String>,
+    pub reason: String,
+    #[serde(default)]
+    pub length: Option<u32>,
+    #[serde(default)]
+    pub created_at: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct PlayerDataTracker {
+    #[serde(default)]
+    pub server: Option<String>,
+    #[serde(default)]
+    pub map: Option<String>,
+    /// The name of the Hypixel proxy the player is connected through. The API doesn't document a fixed set of
+    /// proxy names or a region encoding, so the name is kept as the raw string it sends.
+    #[serde(default)]
+    pub proxy: Option<String>,
+    #[serde(default)]
+    pub last_login: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PlayerDataIPHistory {
+    pub ip: String,
+    pub login_at: String,
+    #[serde(default)]
+    pub logout_at: Option<String>,
+    /// The name of the Hypixel proxy the connection went through, in the same form as [`PlayerDataTracker::proxy`].
+    #[serde(default)]
+    pub connection_proxy: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct PlayerData {
+    pub uuid: String,
+    #[serde(default)]
+    pub nickname_history: Vec<PlayerDataNicknameHistory>,
+    /// The player's infractions in the order the API lists them, which it doesn't document or guarantee. See
+    /// [`PlayerData::infractions_sorted`] for a deterministic order.
+    #[serde(default)]
+    pub infractions: Vec<PlayerDataInfractions>,
+    pub tracker: PlayerDataTracker,
+    /// `None` when the key isn't permitted to see IP addresses, and an empty vector when the player has no IP
+    /// history. The API doesn't signal the missing permission any other way. See
+    /// [`PlayerData::ip_history_or_empty`] when the difference doesn't matter.
+    #[serde(default)]
+    pub ip_history: Option<Vec<PlayerDataIPHistory>>,
+    /// Fields this crate doesn't model yet, captured with the `capture-unknown` feature.
+    #[cfg(feature = "capture-unknown")]
+    #[serde(flatten, deserialize_with = "unknown_fields::<PlayerData, _>")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+pub struct KeyEndpoints {
+    pub id: String,
+    pub version: i8,
+    #[serde(deserialize_with = "tolerant_bool")]
+    pub status: bool,
+}
+
+#[derive(Deserialize)]
+pub struct KeyData {
+    pub key: String,
+    #[serde(deserialize_with = "tolerant_bool")]
+    pub valid: bool,
+    #[serde(deserialize_with = "tolerant_bool")]
+    pub active: bool,
+    #[serde(default)]
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub expires_at: Option<String>,
+    #[serde(default)]
+    pub owner_cactiveconnections_id: Option<ConnectionsId>,
+    #[serde(default)]
+    pub endpoints: Vec<KeyEndpoints>,
+    /// Fields this crate doesn't model yet, captured with the `capture-unknown` feature.
+    #[cfg(feature = "capture-unknown")]
+    #[serde(flatten, deserialize_with = "unknown_fields::<KeyData, _>")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A borrowed view of [`NicknameHistory`].
+#[cfg_attr(
+    feature = "client",
+    doc = "",
+    doc = "See [`Client::nickname_history_borrowed`](crate::Client::nickname_history_borrowed)."
+)]
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NicknameHistoryRef<'a> {
+    #[serde(borrow)]
+    pub uuid: Cow<'a, str>,
+    #[serde(borrow)]
+    pub nickname: Cow<'a, str>,
+    #[serde(deserialize_with = "tolerant_bool")]
+    pub active: bool,
+    #[serde(borrow)]
+    pub created_at: Cow<'a, str>,
+    /// When the entry was voided, `None` while it hasn't been, as in [`NicknameHistory::voided_at`].
+    #[serde(default, borrow, deserialize_with = "empty_cow_as_none")]
+    pub voided_at: Option<Cow<'a, str>>,
+}
+
+/// A borrowed view of [`StaffTracker`].
+#[cfg_attr(
+    feature = "client",
+    doc = "",
+    doc = "See [`Client::staff_tracker_borrowed`](crate::Client::staff_tracker_borrowed)."
+)]
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StaffTrackerRef<'a> {
+    #[serde(borrow)]
+    pub uuid: Cow<'a, str>,
+    #[serde(borrow)]
+    pub rank: Cow<'a, str>,
+    #[serde(default, deserialize_with = "tolerant_option_bool")]
+    pub online: Option<bool>,
+}
+
+/// Data along with whether it was served from the local cache, and the rate limit, warnings and headers reported
+/// with it.
+#[cfg_attr(
+    feature = "client",
+    doc = "",
+    doc = "See [`ClientBuilder::local_cache`](crate::ClientBuilder::local_cache)."
+)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cached<T> {
+    pub data: T,
+    pub from_cache: bool,
+    /// `None` if the response had no rate limit headers.
+    pub rate_limit: Option<RateLimit>,
+    /// Non-fatal errors the API sent in `errors` alongside the data of a successful response, such as when part of
+    /// the data couldn't be collected. The API doesn't document when it does this, so this is usually empty; the
+    /// methods returning the data alone discard these.
+    pub warnings: Vec<InternalError>,
+    /// The headers of the response the data came from. These commonly include `Content-Type`, `Date`, an `ETag`
+    /// when the response can be revalidated, and the `X-RateLimit-*` headers read into `rate_limit`, along with
+    /// any added by the proxies in front of the API. When the data was revalidated with a `304 Not Modified`,
+    /// these are the headers of that response, and they are empty when no request was sent, such as within
+    /// [`ClientBuilder::cache_ttl`](crate::ClientBuilder::cache_ttl) or from
+    /// [`Client::with_stub`](crate::Client::with_stub).
+    #[cfg(feature = "client")]
+    pub headers: reqwest::header::HeaderMap,
+}
+
+/// The rate limit reported by the `X-RateLimit-Limit`, `X-RateLimit-Remaining` and `X-RateLimit-Reset` response
+/// headers, each `None` if its header was missing or unreadable.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RateLimit {
+    /// The number of requests allowed per window.
+    pub limit: Option<u64>,
+    /// The number of requests left in the current window.
+    pub remaining: Option<u64>,
+    /// The time until the window resets, read as a number of seconds.
+    pub reset: Option<Duration>,
+}
+
+/// An inconsistency between two entries of a nickname history, see [`nickname_anomalies`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NicknameAnomaly<'a> {
+    /// Two entries were created at the same time.
+    DuplicateCreatedAt(&'a NicknameHistory, &'a NicknameHistory),
+    /// Two entries were in use over overlapping periods.
+    OverlappingActive(&'a NicknameHistory, &'a NicknameHistory),
+}
+
+/// The staff whose online status changed between two polls, by uuid.
+#[cfg_attr(
+    feature = "client",
+    doc = "",
+    doc = "See [`Client::watch_staff`](crate::Client::watch_staff)."
+)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StaffDiff {
+    pub went_online: HashSet<String>,
+    pub went_offline: HashSet<String>,
+}
+
+/// How many players a prefetch loaded into the local cache.
+#[cfg_attr(
+    feature = "client",
+    doc = "",
+    doc = "See [`Client::prefetch`](crate::Client::prefetch)."
+)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PrefetchSummary {
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+/// A nickname's history split by each entry's `active` flag.
+#[cfg_attr(
+    feature = "client",
+    doc = "",
+    doc = "See [`Client::nickname_history_split`](crate::Client::nickname_history_split)."
+)]
+///
+/// Both vectors keep the order the entries were returned in.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NicknameHistoryResult {
+    pub active: Vec<NicknameHistory>,
+    pub historical: Vec<NicknameHistory>,
+}
+
+/// The changes between two snapshots of a player's data, see [`PlayerData::diff`].
+///
+/// Each vector references entries of whichever snapshot they appear in, keeping that snapshot's order.
+#[derive(Debug, Clone, Default)]
+pub struct PlayerDataDiff<'a> {
+    /// Infractions of the newer snapshot whose id isn't in the older one.
+    pub added_infractions: Vec<&'a PlayerDataInfractions>,
+    /// Infractions of the older snapshot whose id isn't in the newer one, such as after an appeal.
+    pub removed_infractions: Vec<&'a PlayerDataInfractions>,
+    /// Nickname entries of the newer snapshot with a nickname and `created_at` not in the older one.
+    pub added_nicknames: Vec<&'a PlayerDataNicknameHistory>,
+    /// Nickname entries of the newer snapshot which were active in the older one but no longer are.
+    pub voided_nicknames: Vec<&'a PlayerDataNicknameHistory>,
+    /// IP history entries of the newer snapshot with an IP and `login_at` not in the older one. Always empty when
+    /// either snapshot's IP history was withheld, as there is nothing to compare.
+    pub added_ip_history: Vec<&'a PlayerDataIPHistory>,
+}
+
+/// The id of an account in the Cactive Connections system.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct ConnectionsId(pub String);
+
+/// The id of a punishment, such as `C256D602`: exactly eight ASCII letters or digits.
+///
+/// Parsing rejects anything else with an `invalid-punishment-id` error, so typos are caught without a request.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PunishmentId(String);
+
+/// A staff member's rank, see [`StaffTracker::staff_rank`].
+///
+/// The API doesn't document its rank names, so the known ranks are matched case-insensitively under their common
+/// spellings, such as `MOD` and `MODERATOR`, and any other rank is kept verbatim in `Other`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum StaffRank {
+    Owner,
+    Admin,
+    GameMaster,
+    Moderator,
+    Helper,
+    Other(String),
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StaffTracker {
+    pub uuid: String,
+    pub rank: String,
+    #[serde(default, deserialize_with = "tolerant_option_bool")]
+    pub online: Option<bool>,
+    /// Fields this crate doesn't model yet, captured with the `capture-unknown` feature.
+    #[cfg(feature = "capture-unknown")]
+    #[serde(flatten, deserialize_with = "unknown_fields::<StaffTracker, _>")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+pub struct APIData<T> {
+    pub success: bool,
+    pub id: String,
+    pub data: Option<T>,
+    pub errors: Option<Vec<APIError>>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TolerantBool {
+    Bool(bool),
+    Integer(u64),
+    String(String),
+}
+
+fn tolerant_bool<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    match TolerantBool::deserialize(deserializer)? {
+        TolerantBool::Bool(value) => Ok(value),
+        TolerantBool::Integer(0) => Ok(false),
+        TolerantBool::Integer(1) => Ok(true),
+        TolerantBool::String(value) if value == "true" || value == "1" => Ok(true),
+        TolerantBool::String(value) if value == "false" || value == "0" => Ok(false),
+        _ => Err(de::Error::custom(
+            "expected a boolean, 0/1 or \"true\"/\"false\"",
+        )),
+    }
+}
+
+fn empty_string_as_none<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.filter(|value| !value.is_empty()))
+}
+
+fn empty_cow_as_none<'de: 'a, 'a, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Cow<'a, str>>, D::Error> {
+    #[derive(Deserialize)]
+    struct Borrowed<'a>(#[serde(borrow)] Cow<'a, str>);
+
+    Ok(Option::<Borrowed>::deserialize(deserializer)?
+        .map(|Borrowed(value)| value)
+        .filter(|value| !value.is_empty()))
+}
+
+fn tolerant_option_bool<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<bool>, D::Error> {
+    #[derive(Deserialize)]
+    struct Wrapper(#[serde(deserialize_with = "tolerant_bool")] bool);
+
+    Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|Wrapper(value)| value))
+}
+
+/// Nickname history entries are ordered by their parsed `created_at`, with unreadable timestamps first and
+/// compared as strings, then by `uuid` as the tie-breaker and the remaining fields to stay consistent with `Eq`.
+impl Ord for NicknameHistory {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let ordering = parse_timestamp(&self.created_at)
+            .ok()
+            .cmp(&parse_timestamp(&other.created_at).ok())
+            .then_with(|| self.created_at.cmp(&other.created_at))
+            .then_with(|| self.uuid.cmp(&other.uuid))
+            .then_with(|| self.nickname.cmp(&other.nickname))
+            .then_with(|| self.active.cmp(&other.active))
+            .then_with(|| self.voided_at.cmp(&other.voided_at));
+        #[cfg(feature = "capture-unknown")]
+        let ordering = ordering.then_with(|| compare_extra(&self.extra, &other.extra));
+        ordering
+    }
+}
+
+impl PartialOrd for NicknameHistory {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Deserialize the fields a type doesn't model, warning about any the process hasn't warned about before.
+///
+/// A field the crate doesn't know about means the API's schema has drifted from the one this crate targets, so
+/// the first time each is seen on a type, a warning naming it is emitted through `tracing` or `log` when either
+/// feature is enabled. Later responses carrying the same field stay quiet, so a schema change logs once rather
+/// than on every request.
+#[cfg(feature = "capture-unknown")]
+fn unknown_fields<'de, T, D>(
+    deserializer: D,
+) -> Result<serde_json::Map<String, serde_json::Value>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let extra = serde_json::Map::deserialize(deserializer)?;
+    let type_name = std::any::type_name::<T>()
+        .rsplit("::")
+        .next()
+        .unwrap_or_default();
+    let fields = newly_unknown_fields(type_name, extra.keys());
+    if !fields.is_empty() {
+        log_warn!(
+            "the API returned fields {type_name} doesn't model, so this crate may be outdated: {}",
+            fields.join(", ")
+        );
+    }
+    Ok(extra)
+}
+
+/// Record the unknown fields seen on a type, returning those not seen before in this process.
+#[cfg(feature = "capture-unknown")]
+fn newly_unknown_fields<'a>(
+    type_name: &str,
+    fields: impl Iterator<Item = &'a String>,
+) -> Vec<&'a str> {
+    static SEEN: std::sync::OnceLock<std::sync::Mutex<HashSet<String>>> =
+        std::sync::OnceLock::new();
+    let mut seen = SEEN.get_or_init(Default::default).lock().unwrap();
+    fields
+        .filter(|field| seen.insert(format!("{type_name}.{field}")))
+        .map(String::as_str)
+        .collect()
+}
+
+/// Compare captured unknown fields by their JSON text, since JSON values aren't `Ord`.
+#[cfg(feature = "capture-unknown")]
+fn compare_extra(
+    a: &serde_json::Map<String, serde_json::Value>,
+    b: &serde_json::Map<String, serde_json::Value>,
+) -> Ordering {
+    let text = |extra: &serde_json::Map<String, serde_json::Value>| {
+        extra
+            .iter()
+            .map(|(key, value)| (key.clone(), value.to_string()))
+            .collect::<Vec<_>>()
+    };
+    text(a).cmp(&text(b))
+}
+
+/// The fields shared by [`NicknameHistory`] and [`PlayerDataNicknameHistory`], for handling entries from either
+/// endpoint the same way.
+pub trait NicknameEntry {
+    fn nickname(&self) -> &str;
+    fn created_at(&self) -> &str;
+    /// Whether the nickname is currently in use, where an unknown status counts as not in use.
+    fn is_active(&self) -> bool;
+}
+
+impl NicknameEntry for NicknameHistory {
+    fn nickname(&self) -> &str {
+        &self.nickname
+    }
+
+    fn created_at(&self) -> &str {
+        &self.created_at
+    }
+
+    fn is_active(&self) -> bool {
+        self.active
+    }
+}
+
+impl NicknameEntry for PlayerDataNicknameHistory {
+    fn nickname(&self) -> &str {
+        &self.nickname
+    }
+
+    fn created_at(&self) -> &str {
+        &self.created_at
+    }
+
+    fn is_active(&self) -> bool {
+        self.is_active()
+    }
+}
+
+impl PlayerDataNicknameHistory {
+    /// Whether the nickname is currently in use, treating an `active` of `None` as `false`.
+    pub fn is_active(&self) -> bool {
+        self.active.unwrap_or(false)
+    }
+}
+
+impl NicknameHistory {
+    /// Whether the nickname is currently in use, the same as `active`.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// The period the entry was in use, ending at `voided_at`, or open-ended while it is active or `voided_at` is
+    /// missing or unreadable. `None` if `created_at` is unreadable.
+    fn window(&self) -> Option<(SystemTime, Option<SystemTime>)> {
+        let created_at = parse_timestamp(&self.created_at).ok()?;
+        let voided_at = match self.active {
+            true => None,
+            false => self
+                .voided_at
+                .as_deref()
+                .and_then(|voided_at| parse_timestamp(voided_at).ok()),
+        };
+        Some((created_at, voided_at))
+    }
+}
+
+/// Detect entries of a nickname history which can't both be right: entries created at the same time, and
+/// entries in use over overlapping periods, which would mean the nickname was held twice at once.
+///
+/// Each pair is reported once, in the order the entries appear, and entries with an unreadable `created_at`
+/// are only checked for duplicates.
+///
+/// # Examples
+///
+/// ```rust
+/// use cactive_hypixel_api::nickname_anomalies;
+///
+/// let history = client.nickname_history(nickname).await?;
+/// for anomaly in nickname_anomalies(&history) {
+///     println!("{anomaly:?}");
+/// }
+/// ```
+pub fn nickname_anomalies(history: &[NicknameHistory]) -> Vec<NicknameAnomaly<'_>> {
+    let mut anomalies = Vec::new();
+    for (index, first) in history.iter().enumerate() {
+        for second in &history[index + 1..] {
+            if first.created_at == second.created_at {
+                anomalies.push(NicknameAnomaly::DuplicateCreatedAt(first, second));
+            } else if let (Some((first_start, first_end)), Some((second_start, second_end))) =
+                (first.window(), second.window())
+            {
+                if first_end.is_none_or(|end| second_start < end)
+                    && second_end.is_none_or(|end| first_start < end)
+                {
+                    anomalies.push(NicknameAnomaly::OverlappingActive(first, second));
+                }
+            }
+        }
+    }
+    anomalies
+}
+
+/// Group a nickname history by player uuid, giving each player the `(created_at, voided_at)` windows they held
+/// the nickname for, in chronological order.
+///
+/// A window's `voided_at` is `None` while the entry is active or hasn't been voided, and either way the window is
+/// still open.
+///
+/// # Examples
+///
+/// ```rust
+/// use cactive_hypixel_api::nickname_timelines;
+///
+/// let history = client.nickname_history(nickname).await?;
+/// for (uuid, windows) in nickname_timelines(&history) {
+///     println!("{uuid} held it {} times", windows.len());
+/// }
+/// ```
+pub fn nickname_timelines(
+    history: &[NicknameHistory],
+) -> BTreeMap<&str, Vec<(&str, Option<&str>)>> {
+    let mut entries = history.iter().collect::<Vec<_>>();
+    entries.sort();
+
+    let mut timelines = BTreeMap::<_, Vec<_>>::new();
+    for entry in entries {
+        let voided_at = match entry.active {
+            true => None,
+            false => entry.voided_at.as_deref(),
+        };
+        timelines
+            .entry(entry.uuid.as_str())
+            .or_default()
+            .push((entry.created_at.as_str(), voided_at));
+    }
+    timelines
+}
+
+/// The uuids of every player in a nickname history, without duplicates, in the order they first appear.
+pub fn players(history: &[NicknameHistory]) -> Vec<&str> {
+    let mut seen = HashSet::new();
+    history
+        .iter()
+        .map(|entry| entry.uuid.as_str())
+        .filter(|uuid| seen.insert(*uuid))
+        .collect()
+}
+
+/// The number of different players in a nickname history.
+pub fn distinct_players(history: &[NicknameHistory]) -> usize {
+    history
+        .iter()
+        .map(|entry| entry.uuid.as_str())
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+/// Find the periods during which nobody was using the nickname, each as the `voided_at` that left it unused
+/// and the `created_at` of the entry that next took it.
+///
+/// Entries are considered in order of `created_at` whatever order they are given in, and overlapping entries
+/// count as one period of use, so the nickname is only unused once every earlier entry has been voided. Entries
+/// with an unreadable `created_at` are skipped, and active entries or ones with a missing or unreadable
+/// `voided_at` are in use from then on, so no later periods are reported.
+///
+/// # Examples
+///
+/// ```rust
+/// use cactive_hypixel_api::void_periods;
+///
+/// let history = client.nickname_history(nickname).await?;
+/// for (voided_at, reactivated_at) in void_periods(&history) {
+///     println!("unused from {voided_at} until {reactivated_at}");
+/// }
+/// ```
+pub fn void_periods(history: &[NicknameHistory]) -> Vec<(&str, &str)> {
+    let mut entries = history
+        .iter()
+        .filter_map(|entry| Some((entry.window()?, entry)))
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|((created_at, _), _)| *created_at);
+
+    let mut periods = Vec::new();
+    let mut in_use_until: Option<(SystemTime, &str)> = None;
+    for ((created_at, voided_at), entry) in entries {
+        if let Some((until, voided)) = in_use_until {
+            if created_at > until {
+                periods.push((voided, entry.created_at.as_str()));
+            }
+        }
+        match (voided_at, entry.voided_at.as_deref()) {
+            (Some(voided_at), Some(voided)) => {
+                if in_use_until.is_none_or(|(until, _)| voided_at > until) {
+                    in_use_until = Some((voided_at, voided));
+                }
+            }
+            _ => break,
+        }
+    }
+    periods
+}
+
+impl PlayerDataInfractions {
+    /// Whether the infraction is still in effect: permanent infractions (`length` of `None`) always are, and timed
+    /// infractions are while fewer than `length` seconds have passed since `created_at`. Timed infractions
+    /// without a readable `created_at` can't be placed in time, so they are treated as no longer in effect.
+    pub fn is_active(&self) -> bool {
+        let length = match self.duration() {
+            Some(length) => length,
+            None => return true,
+        };
+        self.created_at
+            .as_deref()
+            .and_then(|created_at| parse_timestamp(created_at).ok())
+            .and_then(|created_at| created_at.checked_add(length))
+            .is_some_and(|expires_at| expires_at > SystemTime::now())
+    }
+
+    /// How long the infraction lasts, from `length` in seconds, or `None` if it is permanent.
+    ///
+    /// Every `u32` number of seconds fits in a `Duration`, so this can't overflow, and adding it to a time is
+    /// checked by [`PlayerDataInfractions::is_active`].
+    pub fn duration(&self) -> Option<Duration> {
+        self.length.map(|length| Duration::from_secs(length.into()))
+    }
+
+    /// The infraction as punishment data, taking the `uuid` of the player it was listed under.
+    pub fn to_punishment(&self, uuid: &str) -> PunishmentData {
+        PunishmentData {
+            id: self.id.clone(),
+            punishment_type: self.punishment_type.clone(),
+            uuid: uuid.to_owned(),
+            executor: self.executor.clone(),
+            reason: self.reason.clone(),
+            length: self.length,
+            #[cfg(feature = "capture-unknown")]
+            extra: Default::default(),
+        }
+    }
+}
+
+impl PunishmentData {
+    /// How long the punishment lasts, the same as [`PlayerDataInfractions::duration`].
+    pub fn duration(&self) -> Option<Duration> {
+        self.length.map(|length| Duration::from_secs(length.into()))
+    }
+
+    /// The punishment as a player's infraction, dropping the `uuid`. Punishment data doesn't include when the
+    /// punishment was created, so `created_at` is `None`.
+    pub fn to_infraction(&self) -> PlayerDataInfractions {
+        PlayerDataInfractions {
+            id: self.id.clone(),
+            punishment_type: self.punishment_type.clone(),
+            executor: self.executor.clone(),
+            reason: self.reason.clone(),
+            length: self.length,
+            created_at: None,
+        }
+    }
+}
+
+impl PlayerData {
+    /// The infractions still in effect, following the rules of [`PlayerDataInfractions::is_active`].
+    pub fn active_punishments(&self) -> Vec<&PlayerDataInfractions> {
+        self.infractions
+            .iter()
+            .filter(|infraction| infraction.is_active())
+            .collect()
+    }
+
+    /// The combined length of every timed infraction, summing each `length` in seconds. Permanent infractions
+    /// (`length` of `None`) have no length to add, so they are excluded, and a player with only permanent or no
+    /// infractions has a total of zero. The sum saturates at [`Duration::MAX`] rather than overflowing.
+    pub fn total_punishment_duration(&self) -> Duration {
+        self.infractions
+            .iter()
+            .filter_map(PlayerDataInfractions::duration)
+            .fold(Duration::ZERO, |total, duration| {
+                total.checked_add(duration).unwrap_or(Duration::MAX)
+            })
+    }
+
+    /// The IP history, or an empty slice when it was withheld because the key isn't permitted to see it. Check
+    /// [`PlayerData::ip_history`] directly to tell the two apart.
+    pub fn ip_history_or_empty(&self) -> &[PlayerDataIPHistory] {
+        self.ip_history.as_deref().unwrap_or_default()
+    }
+
+    /// The servers and proxies the player was recently seen on, most recent first and without duplicates.
+    ///
+    /// The result starts with the tracker's current `server` and `proxy`, followed by the `connection_proxy` of
+    /// each IP history entry from the latest `login_at`, with unreadable login times last. The IP history only
+    /// records proxies, so if it is absent the result is at most the tracker's server and proxy.
+    pub fn recent_servers(&self) -> Vec<&str> {
+        let mut history = self.ip_history.iter().flatten().collect::<Vec<_>>();
+        history.sort_by_key(|entry| Reverse(parse_timestamp(&entry.login_at).ok()));
+
+        let mut servers = Vec::new();
+        let candidates = [&self.tracker.server, &self.tracker.proxy]
+            .into_iter()
+            .chain(history.into_iter().map(|entry| &entry.connection_proxy));
+        for server in candidates.flatten() {
+            if !servers.contains(&server.as_str()) {
+                servers.push(server.as_str());
+            }
+        }
+        servers
+    }
+
+    /// The infractions oldest first by `created_at`, with ties and infractions without a readable `created_at`
+    /// ordered by id. Infractions without a `created_at` can't be placed in time, so they come last.
+    pub fn infractions_sorted(&self) -> Vec<&PlayerDataInfractions> {
+        let mut infractions = self.infractions.iter().collect::<Vec<_>>();
+        infractions.sort_by_cached_key(|infraction| {
+            let created_at = infraction
+                .created_at
+                .as_deref()
+                .and_then(|created_at| parse_timestamp(created_at).ok());
+            (created_at.is_none(), created_at, infraction.id.as_str())
+        });
+        infractions
+    }
+
+    /// The nickname the player is currently using, the latest created of their active nicknames, or `None` if
+    /// they aren't using one.
+    pub fn current_nickname(&self) -> Option<&str> {
+        self.nickname_history
+            .iter()
+            .filter(|entry| entry.is_active())
+            .max_by_key(|entry| parse_timestamp(&entry.created_at).ok())
+            .map(|entry| entry.nickname.as_str())
+    }
+
+    /// The IDs of every infraction.
+    #[cfg_attr(
+        feature = "client",
+        doc = "",
+        doc = "See [`Client::punishment_data`](crate::Client::punishment_data)."
+    )]
+    pub fn punishment_ids(&self) -> Vec<&str> {
+        self.infractions
+            .iter()
+            .map(|infraction| infraction.id.as_str())
+            .collect()
+    }
+
+    /// What changed from this snapshot of the player's data to a newer one, `other`. Entries are matched by
+    /// value rather than position, so a reordered response reports no changes, and a logout recorded on an
+    /// existing IP history entry isn't a new entry.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let latest = client.player_data(uuid).await.map_err(Errors)?;
+    /// for infraction in previous.diff(&latest).added_infractions {
+    ///     println!("New {} for {}", infraction.punishment_type, infraction.reason);
+    /// }
+    /// ```
+    pub fn diff<'a>(&'a self, other: &'a PlayerData) -> PlayerDataDiff<'a> {
+        let ids = |data: &'a PlayerData| {
+            data.infractions
+                .iter()
+                .map(|infraction| infraction.id.as_str())
+                .collect::<HashSet<_>>()
+        };
+        let (old_ids, new_ids) = (ids(self), ids(other));
+
+        let nickname_key = |entry: &'a PlayerDataNicknameHistory| {
+            (entry.nickname.as_str(), entry.created_at.as_str())
+        };
+        let old_nicknames = self
+            .nickname_history
+            .iter()
+            .map(|entry| (nickname_key(entry), entry.is_active()))
+            .collect::<BTreeMap<_, _>>();
+
+        let added_ip_history = match (&self.ip_history, &other.ip_history) {
+            (Some(old), Some(new)) => {
+                let seen = old
+                    .iter()
+                    .map(|entry| (entry.ip.as_str(), entry.login_at.as_str()))
+                    .collect::<HashSet<_>>();
+                new.iter()
+                    .filter(|entry| !seen.contains(&(entry.ip.as_str(), entry.login_at.as_str())))
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+
+        PlayerDataDiff {
+            added_infractions: other
+                .infractions
+                .iter()
+                .filter(|infraction| !old_ids.contains(infraction.id.as_str()))
+                .collect(),
+            removed_infractions: self
+                .infractions
+                .iter()
+                .filter(|infraction| !new_ids.contains(infraction.id.as_str()))
+                .collect(),
+            added_nicknames: other
+                .nickname_history
+                .iter()
+                .filter(|entry| !old_nicknames.contains_key(&nickname_key(entry)))
+                .collect(),
+            voided_nicknames: other
+                .nickname_history
+                .iter()
+                .filter(|entry| {
+                    old_nicknames.get(&nickname_key(entry)) == Some(&true) && !entry.is_active()
+                })
+                .collect(),
+            added_ip_history,
+        }
+    }
+
+    /// The column names of [`PlayerData::to_csv_record`], for the first row of a spreadsheet:
+    ///
+    /// | Column | Value |
+    /// | --- | --- |
+    /// | `uuid` | [`PlayerData::uuid`] |
+    /// | `current_nickname` | [`PlayerData::current_nickname`], empty when the player isn't using one |
+    /// | `infraction_count` | The number of [`PlayerData::infractions`], including expired ones |
+    /// | `last_login` | [`PlayerDataTracker::last_login`] as the API returned it, empty when unknown |
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let mut writer = csv::Writer::from_path("players.csv")?;
+    /// writer.write_record(PlayerData::csv_header())?;
+    /// for player in players {
+    ///     writer.write_record(player.to_csv_record())?;
+    /// }
+    /// ```
+    #[cfg(feature = "csv")]
+    pub fn csv_header() -> [&'static str; 4] {
+        ["uuid", "current_nickname", "infraction_count", "last_login"]
+    }
+
+    /// The player flattened into one row of the columns listed in [`PlayerData::csv_header`], in the same order.
+    /// Values aren't quoted or escaped, which is left to the CSV writer.
+    #[cfg(feature = "csv")]
+    pub fn to_csv_record(&self) -> Vec<String> {
+        vec![
+            self.uuid.clone(),
+            self.current_nickname().unwrap_or_default().to_owned(),
+            self.infractions.len().to_string(),
+            self.tracker.last_login.clone().unwrap_or_default(),
+        ]
+    }
+}
+
+impl PlayerDataTracker {
+    /// The time of the player's last login, or `None` if it is unknown.
+    #[cfg(feature = "chrono")]
+    pub fn last_login_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        parse_timestamp(self.last_login.as_deref()?)
+            .ok()
+            .map(chrono::DateTime::from)
+    }
+
+    /// The time of the player's last login in milliseconds since the Unix epoch, see [`parse_timestamp_millis`],
+    /// or `None` if it is unknown.
+    pub fn last_login_millis(&self) -> Option<i64> {
+        parse_timestamp_millis(self.last_login.as_deref()?).ok()
+    }
+
+    /// Whether the player last logged in within the given duration, which is never the case when it is unknown.
+    pub fn seen_within(&self, duration: Duration) -> bool {
+        match self
+            .last_login
+            .as_deref()
+            .and_then(|last_login| parse_timestamp(last_login).ok())
+        {
+            Some(last_login) => SystemTime::now()
+                .duration_since(last_login)
+                .map_or(true, |elapsed| elapsed <= duration),
+            None => false,
+        }
+    }
+}
+
+impl PlayerDataDiff<'_> {
+    /// Whether nothing changed between the snapshots.
+    pub fn is_empty(&self) -> bool {
+        self.added_infractions.is_empty()
+            && self.removed_infractions.is_empty()
+            && self.added_nicknames.is_empty()
+            && self.voided_nicknames.is_empty()
+            && self.added_ip_history.is_empty()
+    }
+}
+
+impl From<Vec<NicknameHistory>> for NicknameHistoryResult {
+    fn from(history: Vec<NicknameHistory>) -> Self {
+        let (active, historical) = history.into_iter().partition(|entry| entry.active);
+        NicknameHistoryResult { active, historical }
+    }
+}
+
+impl ConnectionsId {
+    /// The id as a number, or `None` if it isn't numeric.
+    pub fn to_u64(&self) -> Option<u64> {
+        self.0.parse().ok()
+    }
+}
+
+impl fmt::Display for ConnectionsId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for ConnectionsId {
+    type Err = Infallible;
+
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        Ok(ConnectionsId(id.to_owned()))
+    }
+}
+
+impl PunishmentId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for PunishmentId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for PunishmentId {
+    type Err = InternalError;
+
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        match id.len() == 8 && id.bytes().all(|byte| byte.is_ascii_alphanumeric()) {
+            true => Ok(PunishmentId(id.to_owned())),
+            false => Err(InternalError::internal(
+                "invalid-punishment-id",
+                400,
+                format!("{id:?} is not eight letters or digits"),
+            )),
+        }
+    }
+}
+
+impl From<&str> for StaffRank {
+    fn from(rank: &str) -> Self {
+        match rank.to_ascii_uppercase().as_str() {
+            "OWNER" => StaffRank::Owner,
+            "ADMIN" | "ADMINISTRATOR" => StaffRank::Admin,
+            "GAME_MASTER" | "GM" => StaffRank::GameMaster,
+            "MOD" | "MODERATOR" => StaffRank::Moderator,
+            "HELPER" => StaffRank::Helper,
+            _ => StaffRank::Other(rank.to_owned()),
+        }
+    }
+}
+
+impl StaffTracker {
+    /// The staff member's `rank` as a [`StaffRank`].
+    pub fn staff_rank(&self) -> StaffRank {
+        StaffRank::from(self.rank.as_str())
+    }
+
+    /// Whether the staff member has the given rank. A known rank matches any of its spellings, while
+    /// [`StaffRank::Other`] only matches a `rank` of exactly the same string.
+    pub fn has_rank(&self, rank: StaffRank) -> bool {
+        self.staff_rank() == rank
+    }
+}
+
+/// The staff members with the given rank, in their original order, see [`StaffTracker::has_rank`].
+///
+/// # Examples
+///
+/// ```rust
+/// use cactive_hypixel_api::{with_rank, StaffRank};
+///
+/// let staff = client.staff_tracker("online".to_owned()).await?;
+/// println!("{} moderators online", with_rank(&staff, StaffRank::Moderator).len());
+/// ```
+pub fn with_rank(staff: &[StaffTracker], rank: StaffRank) -> Vec<&StaffTracker> {
+    staff
+        .iter()
+        .filter(|member| member.staff_rank() == rank)
+        .collect()
+}
+
+impl KeyData {
+    /// The version the key uses for an endpoint, such as `"player-data"`, or `None` if it isn't listed.
+    pub fn endpoint_version(&self, id: &str) -> Option<i8> {
+        self.endpoints
+            .iter()
+            .find(|endpoint| endpoint.id == id)
+            .map(|endpoint| endpoint.version)
+    }
+
+    /// The endpoints whose version differs from the API version this crate targets, which may fail to decode.
+    pub fn mismatched_endpoints(&self) -> impl Iterator<Item = &KeyEndpoints> {
+        self.endpoints
+            .iter()
+            .filter(|endpoint| u8::try_from(endpoint.version) != Ok(API_VERSION))
+    }
+
+    /// Whether the key has passed its expiry, which is never the case when `expires_at` is absent or unreadable.
+    pub fn is_expired(&self) -> bool {
+        self.time_until_expiry() == Some(Duration::ZERO)
+    }
+
+    /// Time remaining before the key expires, zero once it has expired, or `None` if it never expires.
+    pub fn time_until_expiry(&self) -> Option<Duration> {
+        let expires_at = parse_timestamp(self.expires_at.as_deref()?).ok()?;
+        Some(
+            expires_at
+                .duration_since(SystemTime::now())
+                .unwrap_or(Duration::ZERO),
+        )
+    }
+}
+
+/// Parse a timestamp in the formats emitted by the API.
+///
+/// Accepted timestamps are `YYYY-MM-DDTHH:MM:SS` (with `T` or a space between the date and time), optionally
+/// followed by a fraction of a second, of which nanosecond precision is kept, and optionally followed by `Z` or a
+/// `+HH:MM`/`-HH:MM` offset. Timestamps without an offset are read as UTC. Any other input returns an
+/// `invalid-timestamp` error, and never panics.
+///
+/// # Examples
+///
+/// ```rust
+/// use cactive_hypixel_api::parse_timestamp;
+///
+/// let login = parse_timestamp("2022-08-05T12:34:56.789Z")?;
+/// ```
+pub fn parse_timestamp(value: &str) -> Result<SystemTime, InternalError> {
+    timestamp_from_bytes(value.as_bytes()).ok_or_else(|| {
+        InternalError::internal(
+            "invalid-timestamp",
+            400,
+            format!("{value:?} is not a recognised timestamp"),
+        )
+    })
+}
+
+/// Parse a timestamp in the formats accepted by [`parse_timestamp`] into milliseconds since the Unix epoch, for
+/// systems expecting numeric timestamps.
+///
+/// The unit is milliseconds, not seconds, and timestamps before 1970 are negative. Sub-millisecond precision is
+/// rounded down, towards the earlier millisecond. This needs no feature and works alongside the `chrono` helpers.
+///
+/// # Examples
+///
+/// ```rust
+/// use cactive_hypixel_api::parse_timestamp_millis;
+///
+/// assert_eq!(parse_timestamp_millis("2022-08-05T12:34:56.789Z")?, 1659702896789);
+/// ```
+pub fn parse_timestamp_millis(value: &str) -> Result<i64, InternalError> {
+    let nanos = match parse_timestamp(value)?.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(since) => since.as_nanos() as i128,
+        Err(before) => -(before.duration().as_nanos() as i128),
+    };
+    Ok(nanos.div_euclid(1_000_000) as i64)
+}
+
+fn timestamp_from_bytes(value: &[u8]) -> Option<SystemTime> {
+    fn number(digits: &[u8]) -> Option<i64> {
+        digits.iter().try_fold(0, |number, digit| {
+            digit
+                .is_ascii_digit()
+                .then(|| number * 10 + i64::from(digit - b'0'))
+        })
+    }
+
+    let (date, rest) = (value.get(..19)?, value.get(19..)?);
+    let [y1, y2, y3, y4, b'-', m1, m2, b'-', d1, d2, b'T' | b't' | b' ', h1, h2, b':', n1, n2, b':', s1, s2] =
+        *date
+    else {
+        return None;
+    };
+    let (year, month, day) = (
+        number(&[y1, y2, y3, y4])?,
+        number(&[m1, m2])?,
+        number(&[d1, d2])?,
+    );
+    let (hour, minute, second) = (number(&[h1, h2])?, number(&[n1, n2])?, number(&[s1, s2])?);
+
+    let leap_year = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        2 if leap_year => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return None,
+    };
+    if !(1..=days_in_month).contains(&day) || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+
+    let (nanos, rest) = match rest {
+        [b'.', fraction @ ..] => {
+            let length = fraction
+                .iter()
+                .take_while(|byte| byte.is_ascii_digit())
+                .count();
+            let digits = &fraction[..length.min(9)];
+            if length == 0 {
+                return None;
+            }
+            (
+                number(digits)? * 10_i64.pow(9 - digits.len() as u32),
+                &fraction[length..],
+            )
+        }
+        _ => (0, rest),
+    };
+
+    let offset = match *rest {
+        [] | [b'Z'] | [b'z'] => 0,
+        [sign @ (b'+' | b'-'), h1, h2, b':', m1, m2] => {
+            let (hours, minutes) = (number(&[h1, h2])?, number(&[m1, m2])?);
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            let offset = hours * 3600 + minutes * 60;
+            if sign == b'-' {
+                -offset
+            } else {
+                offset
+            }
+        }
+        _ => return None,
+    };
+
+    // Days since the unix epoch for the proleptic Gregorian calendar.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+
+    let seconds = days * 86400 + hour * 3600 + minute * 60 + second - offset;
+    let time = match seconds >= 0 {
+        true => SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(seconds as u64))?,
+        false => SystemTime::UNIX_EPOCH.checked_sub(Duration::from_secs(seconds.unsigned_abs()))?,
+    };
+    time.checked_add(Duration::from_nanos(nanos as u64))
+}
+
+#[test]
+fn staff_tracker_dedup_test() {
+    let staff = |online| StaffTracker {
+        uuid: "a".to_owned(),
+        rank: "ADMIN".to_owned(),
+        online,
+        #[cfg(feature = "capture-unknown")]
+        extra: Default::default(),
+    };
+
+    let unique = [staff(Some(true)), staff(Some(true)), staff(None)]
+        .into_iter()
+        .collect::<std::collections::HashSet<_>>();
+    assert_eq!(unique.len(), 2);
+}
+
+#[test]
+fn key_expiry_test() {
+    let key = |expires_at: &str| -> KeyData {
+        serde_json::from_str(&format!(
+            r#"{{"key":"k","valid":true,"active":true,"expires_at":{expires_at},"endpoints":[]}}"#
+        ))
+        .unwrap()
+    };
+
+    assert!(!key("null").is_expired());
+    assert_eq!(key("null").time_until_expiry(), None);
+    assert!(key(r#""2000-01-01T00:00:00.000Z""#).is_expired());
+    assert!(!key(r#""2999-01-01T00:00:00+01:00""#).is_expired());
+    assert!(key(r#""2999-01-01T00:00:00Z""#).time_until_expiry() > Some(Duration::ZERO));
+}
+
+#[test]
+fn missing_optional_fields_test() {
+    let player: PlayerData =
+        serde_json::from_str(r#"{"uuid":"a","tracker":{},"infractions":[{"id":"C256D602","punishment_type":"BAN","reason":"cheating"}]}"#)
+            .unwrap();
+
+    assert!(player.nickname_history.is_empty());
+    assert!(player.ip_history.is_none());
+    assert!(player.tracker.last_login.is_none());
+    assert_eq!(player.infractions[0].length, None);
+}
+
+#[test]
+fn seen_within_test() {
+    let tracker = |last_login: Option<&str>| PlayerDataTracker {
+        server: None,
+        map: None,
+        proxy: None,
+        last_login: last_login.map(str::to_owned),
+    };
+    let day = Duration::from_secs(86400);
+
+    assert!(!tracker(None).seen_within(day));
+    assert!(!tracker(Some("2000-01-01T00:00:00Z")).seen_within(day));
+    assert!(tracker(Some("2999-01-01T00:00:00Z")).seen_within(day));
+}
+
+#[test]
+fn tolerant_bool_test() {
+    let staff = |online: &str| -> Result<StaffTracker, serde_json::Error> {
+        serde_json::from_str(&format!(
+            r#"{{"uuid":"a","rank":"ADMIN","online":{online}}}"#
+        ))
+    };
+
+    for (online, expected) in [
+        ("true", Some(true)),
+        ("false", Some(false)),
+        ("1", Some(true)),
+        ("0", Some(false)),
+        (r#""true""#, Some(true)),
+        (r#""false""#, Some(false)),
+        ("null", None),
+    ] {
+        assert_eq!(staff(online).unwrap().online, expected);
+    }
+    assert!(staff("2").is_err());
+    assert!(staff(r#""yes""#).is_err());
+
+    let endpoint: KeyEndpoints =
+        serde_json::from_str(r#"{"id":"player-data","version":3,"status":"1"}"#).unwrap();
+    assert!(endpoint.status);
+}
+
+#[test]
+fn nickname_history_order_test() {
+    let entry = |uuid: &str, created_at: &str| NicknameHistory {
+        uuid: uuid.to_owned(),
+        nickname: "n".to_owned(),
+        active: false,
+        created_at: created_at.to_owned(),
+        voided_at: None,
+        #[cfg(feature = "capture-unknown")]
+        extra: Default::default(),
+    };
+
+    let mut history = [
+        entry("b", "2022-01-01T00:00:00Z"),
+        entry("a", "2022-01-01T01:00:00+02:00"),
+        entry("a", "2022-01-01T00:00:00Z"),
+        entry("c", "2021-06-01T00:00:00.500Z"),
+    ];
+    history.sort();
+
+    let order = history
+        .iter()
+        .map(|entry| (entry.uuid.as_str(), entry.created_at.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        order,
+        [
+            ("c", "2021-06-01T00:00:00.500Z"),
+            ("a", "2022-01-01T01:00:00+02:00"),
+            ("a", "2022-01-01T00:00:00Z"),
+            ("b", "2022-01-01T00:00:00Z"),
+        ]
+    );
+}
+
+#[test]
+fn connections_id_test() {
+    let key: KeyData = serde_json::from_str(
+        r#"{"key":"k","valid":true,"active":true,"owner_cactiveconnections_id":"1024","endpoints":[]}"#,
+    )
+    .unwrap();
+    let id = key.owner_cactiveconnections_id.unwrap();
+
+    assert_eq!(id, "1024".parse().unwrap());
+    assert_eq!(id.to_string(), "1024");
+    assert_eq!(id.to_u64(), Some(1024));
+}
+
+#[test]
+fn endpoint_version_test() {
+    let key: KeyData = serde_json::from_str(
+        r#"{"key":"k","valid":true,"active":true,"endpoints":[
+            {"id":"player-data","version":3,"status":true},
+            {"id":"staff-tracker","version":2,"status":true}
+        ]}"#,
+    )
+    .unwrap();
+
+    assert_eq!(key.endpoint_version("player-data"), Some(3));
+    assert_eq!(key.endpoint_version("key"), None);
+    assert_eq!(
+        key.mismatched_endpoints()
+            .map(|endpoint| endpoint.id.as_str())
+            .collect::<Vec<_>>(),
+        ["staff-tracker"]
+    );
+}
+
+#[test]
+fn parse_timestamp_test() {
+    let at =
+        |seconds: u64, nanos: u32| Some(SystemTime::UNIX_EPOCH + Duration::new(seconds, nanos));
+
+    for (value, expected) in [
+        ("2022-08-05T12:34:56Z", at(1659702896, 0)),
+        ("2022-08-05T12:34:56.789Z", at(1659702896, 789_000_000)),
+        (
+            "2022-08-05T12:34:56.123456789123Z",
+            at(1659702896, 123_456_789),
+        ),
+        ("2022-08-05 12:34:56", at(1659702896, 0)),
+        ("2022-08-05t12:34:56.5", at(1659702896, 500_000_000)),
+        ("2022-08-05T14:34:56+02:00", at(1659702896, 0)),
+        ("2022-08-05T10:04:56.000-02:30", at(1659702896, 0)),
+        ("2024-02-29T00:00:00Z", at(1709164800, 0)),
+        ("1970-01-01T00:00:00Z", at(0, 0)),
+    ] {
+        assert_eq!(parse_timestamp(value).ok(), expected, "{value}");
+    }
+
+    for value in [
+        "",
+        "2022-08-05",
+        "2022-08-05T12:34",
+        "2022-13-05T12:34:56Z",
+        "2023-02-29T12:34:56Z",
+        "2022-08-05T24:00:00Z",
+        "2022-08-05T12:34:56.Z",
+        "2022-08-05T12:34:56+2:00",
+        "2022-08-05T12:34:56+0é00",
+        "2022-08-05T12:34:56 UTC",
+        "+022-08-05T12:34:56Z",
+    ] {
+        let error = parse_timestamp(value).unwrap_err();
+        assert_eq!(error.r#type, "invalid-timestamp", "{value}");
+    }
+}
+
+#[test]
+fn parse_timestamp_never_panics_test() {
+    let alphabet = "0123456789-:.TZ+ z\u{e9}".chars().collect::<Vec<_>>();
+    let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+    let mut random = |bound: usize| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed as usize % bound
+    };
+
+    for _ in 0..20_000 {
+        let mut value = "2022-08-05T12:34:56.789+01:00".chars().collect::<Vec<_>>();
+        for _ in 0..random(4) {
+            let index = random(value.len() + 1);
+            match random(3) {
+                0 if index < value.len() => value[index] = alphabet[random(alphabet.len())],
+                1 => value.insert(index, alphabet[random(alphabet.len())]),
+                _ => value.truncate(index),
+            }
+        }
+        let _ = parse_timestamp(&value.into_iter().collect::<String>());
+    }
+}
+
+#[test]
+fn active_punishments_test() {
+    let player: PlayerData = serde_json::from_str(
+        r#"{"uuid":"a","tracker":{},"infractions":[
+            {"id":"PERMANENT","punishment_type":"BAN","reason":"cheating"},
+            {"id":"EXPIRED","punishment_type":"MUTE","reason":"spam","length":3600,"created_at":"2000-01-01T00:00:00Z"},
+            {"id":"ACTIVE","punishment_type":"MUTE","reason":"spam","length":3600,"created_at":"2999-01-01T00:00:00Z"},
+            {"id":"UNKNOWN","punishment_type":"MUTE","reason":"spam","length":3600}
+        ]}"#,
+    )
+    .unwrap();
+
+    let active = player
+        .active_punishments()
+        .into_iter()
+        .map(|infraction| infraction.id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(active, ["PERMANENT", "ACTIVE"]);
+}
+
+#[test]
+fn nickname_anomalies_test() {
+    let entry =
+        |uuid: &str, active: bool, created_at: &str, voided_at: Option<&str>| NicknameHistory {
+            uuid: uuid.to_owned(),
+            nickname: "n".to_owned(),
+            active,
+            created_at: created_at.to_owned(),
+            voided_at: voided_at.map(str::to_owned),
+            #[cfg(feature = "capture-unknown")]
+            extra: Default::default(),
+        };
+    let history = [
+        entry(
+            "a",
+            false,
+            "2020-01-01T00:00:00Z",
+            Some("2020-06-01T00:00:00Z"),
+        ),
+        entry(
+            "b",
+            false,
+            "2020-06-01T00:00:00Z",
+            Some("2021-01-01T00:00:00Z"),
+        ),
+        entry(
+            "c",
+            false,
+            "2020-12-01T00:00:00Z",
+            Some("2021-02-01T00:00:00Z"),
+        ),
+        entry("d", true, "2021-03-01T00:00:00Z", None),
+        entry("e", true, "2021-03-01T00:00:00Z", None),
+    ];
+
+    assert_eq!(
+        nickname_anomalies(&history),
+        [
+            NicknameAnomaly::OverlappingActive(&history[1], &history[2]),
+            NicknameAnomaly::DuplicateCreatedAt(&history[3], &history[4]),
+        ]
+    );
+}
+
+#[test]
+fn punishment_id_test() {
+    assert_eq!(
+        "C256D602".parse::<PunishmentId>().unwrap().as_str(),
+        "C256D602"
+    );
+    for id in ["", "C256D60", "C256D6023", "C256-602", "C256D60é"] {
+        let error = id.parse::<PunishmentId>().unwrap_err();
+        assert_eq!(error.r#type, "invalid-punishment-id");
+    }
+}
+
+#[test]
+fn punishment_conversion_test() {
+    let infraction = PlayerDataInfractions {
+        id: "C256D602".to_owned(),
+        punishment_type: "MUTE".to_owned(),
+        executor: Some("a".to_owned()),
+        reason: "spam".to_owned(),
+        length: Some(3600),
+        created_at: Some("2022-08-05T12:34:56Z".to_owned()),
+    };
+
+    let punishment = infraction.to_punishment("b");
+    assert_eq!(punishment.id, "C256D602");
+    assert_eq!(punishment.punishment_type, "MUTE");
+    assert_eq!(punishment.uuid, "b");
+    assert_eq!(punishment.executor.as_deref(), Some("a"));
+    assert_eq!(punishment.reason, "spam");
+    assert_eq!(punishment.length, Some(3600));
+
+    let infraction = punishment.to_infraction();
+    assert_eq!(infraction.id, "C256D602");
+    assert_eq!(infraction.punishment_type, "MUTE");
+    assert_eq!(infraction.executor.as_deref(), Some("a"));
+    assert_eq!(infraction.reason, "spam");
+    assert_eq!(infraction.length, Some(3600));
+    assert_eq!(infraction.created_at, None);
+}
+
+#[test]
+fn nickname_entry_test() {
+    fn active<'a>(entries: &[&'a dyn NicknameEntry]) -> Vec<&'a str> {
+        entries
+            .iter()
+            .filter(|entry| entry.is_active())
+            .map(|entry| entry.nickname())
+            .collect()
+    }
+
+    let history: NicknameHistory = serde_json::from_str(
+        r#"{"uuid":"a","nickname":"first","active":true,"created_at":"","voided_at":""}"#,
+    )
+    .unwrap();
+    let player: PlayerData = serde_json::from_str(
+        r#"{"uuid":"a","tracker":{},"nickname_history":[
+            {"nickname":"second","active":true,"created_at":""},
+            {"nickname":"third","active":false,"created_at":""},
+            {"nickname":"fourth","created_at":""}
+        ]}"#,
+    )
+    .unwrap();
+
+    assert!(!player.nickname_history[2].is_active());
+    let mut entries: Vec<&dyn NicknameEntry> = vec![&history];
+    entries.extend(
+        player
+            .nickname_history
+            .iter()
+            .map(|entry| entry as &dyn NicknameEntry),
+    );
+    assert_eq!(active(&entries), ["first", "second"]);
+}
+
+#[cfg(feature = "capture-unknown")]
+#[test]
+fn capture_unknown_test() {
+    let player: PlayerData =
+        serde_json::from_str(r#"{"uuid":"a","tracker":{},"rank":"MVP_PLUS","karma":10}"#).unwrap();
+    assert_eq!(player.extra["rank"], "MVP_PLUS");
+    assert_eq!(player.extra["karma"], 10);
+    assert!(!player.extra.contains_key("uuid"));
+}
+
+#[test]
+fn nickname_timelines_test() {
+    let history: Vec<NicknameHistory> = serde_json::from_str(
+        r#"[
+            {"uuid":"b","nickname":"n","active":true,"created_at":"2022-01-01T00:00:00Z","voided_at":""},
+            {"uuid":"a","nickname":"n","active":false,"created_at":"2021-01-01T00:00:00Z","voided_at":"2021-06-01T00:00:00Z"},
+            {"uuid":"a","nickname":"n","active":false,"created_at":"2020-01-01T00:00:00Z","voided_at":""}
+        ]"#,
+    )
+    .unwrap();
+
+    let timelines = nickname_timelines(&history);
+    assert_eq!(timelines.len(), 2);
+    assert_eq!(
+        timelines["a"],
+        [
+            ("2020-01-01T00:00:00Z", None),
+            ("2021-01-01T00:00:00Z", Some("2021-06-01T00:00:00Z")),
+        ]
+    );
+    assert_eq!(timelines["b"], [("2022-01-01T00:00:00Z", None)]);
+}
+
+#[test]
+fn empty_voided_at_test() {
+    let entry = |voided_at: &str| -> NicknameHistory {
+        serde_json::from_str(&format!(
+            r#"{{"uuid":"a","nickname":"n","active":false,"created_at":"2020-01-01T00:00:00Z"{voided_at}}}"#
+        ))
+        .unwrap()
+    };
+
+    assert_eq!(entry(r#","voided_at":"""#).voided_at, None);
+    assert_eq!(entry(r#","voided_at":null"#).voided_at, None);
+    assert_eq!(entry("").voided_at, None);
+    assert_eq!(
+        entry(r#","voided_at":"2021-01-01T00:00:00Z""#)
+            .voided_at
+            .as_deref(),
+        Some("2021-01-01T00:00:00Z")
+    );
+}
+
+#[test]
+fn punishment_duration_test() {
+    let infraction: PlayerDataInfractions = serde_json::from_str(&format!(
+        r#"{{"id":"A","punishment_type":"BAN","reason":"r","length":{},"created_at":"9999-12-31T23:59:59Z"}}"#,
+        u32::MAX
+    ))
+    .unwrap();
+
+    assert_eq!(
+        infraction.duration(),
+        Some(Duration::from_secs(u32::MAX.into()))
+    );
+    assert_eq!(
+        infraction.to_punishment("a").duration(),
+        infraction.duration()
+    );
+    assert!(infraction.is_active());
+}
+
+#[test]
+fn recent_servers_test() {
+    let player: PlayerData = serde_json::from_str(
+        r#"{"uuid":"a","tracker":{"server":"mini1A","proxy":"proxy1"},"ip_history":[
+            {"ip":"1.1.1.1","login_at":"2022-01-01T00:00:00Z","connection_proxy":"proxy2"},
+            {"ip":"1.1.1.1","login_at":"not a time","connection_proxy":"proxy4"},
+            {"ip":"1.1.1.1","login_at":"2022-02-01T00:00:00Z","connection_proxy":"proxy1"},
+            {"ip":"1.1.1.1","login_at":"2021-01-01T00:00:00Z","connection_proxy":"proxy3"}
+        ]}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        player.recent_servers(),
+        ["mini1A", "proxy1", "proxy2", "proxy3", "proxy4"]
+    );
+
+    let player: PlayerData =
+        serde_json::from_str(r#"{"uuid":"a","tracker":{"server":"mini1A"}}"#).unwrap();
+    assert_eq!(player.recent_servers(), ["mini1A"]);
+}
+
+#[test]
+fn void_periods_test() {
+    let history: Vec<NicknameHistory> = serde_json::from_str(
+        r#"[
+            {"uuid":"d","nickname":"n","active":true,"created_at":"2021-06-01T00:00:00Z"},
+            {"uuid":"c","nickname":"n","active":false,"created_at":"2020-12-01T00:00:00Z","voided_at":"2021-03-01T00:00:00Z"},
+            {"uuid":"a","nickname":"n","active":false,"created_at":"2020-01-01T00:00:00Z","voided_at":"2020-06-01T00:00:00Z"},
+            {"uuid":"b","nickname":"n","active":false,"created_at":"2020-09-01T00:00:00Z","voided_at":"2021-01-01T00:00:00Z"},
+            {"uuid":"e","nickname":"n","active":false,"created_at":"2022-01-01T00:00:00Z","voided_at":"2022-02-01T00:00:00Z"}
+        ]"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        void_periods(&history),
+        [
+            ("2020-06-01T00:00:00Z", "2020-09-01T00:00:00Z"),
+            ("2021-03-01T00:00:00Z", "2021-06-01T00:00:00Z"),
+        ]
+    );
+}
+
+#[test]
+fn players_test() {
+    let history: Vec<NicknameHistory> = serde_json::from_str(
+        r#"[
+            {"uuid":"b","nickname":"n","active":false,"created_at":""},
+            {"uuid":"a","nickname":"n","active":false,"created_at":""},
+            {"uuid":"b","nickname":"n","active":false,"created_at":""},
+            {"uuid":"c","nickname":"n","active":true,"created_at":""},
+            {"uuid":"a","nickname":"n","active":false,"created_at":""}
+        ]"#,
+    )
+    .unwrap();
+
+    assert_eq!(players(&history), ["b", "a", "c"]);
+    assert_eq!(distinct_players(&history), 3);
+    assert_eq!(distinct_players(&[]), 0);
+}
+
+#[test]
+fn fixtures_test() {
+    fn data<T: serde::de::DeserializeOwned>(json: &str) -> T {
+        let envelope: APIData<T> = serde_json::from_str(json).unwrap();
+        assert!(envelope.success);
+        envelope.data.unwrap()
+    }
+
+    let full: Vec<NicknameHistory> =
+        data(include_str!("../tests/fixtures/nickname_history_full.json"));
+    assert!(full.iter().all(|entry| entry.voided_at.is_some()));
+    let minimal: Vec<NicknameHistory> = data(include_str!(
+        "../tests/fixtures/nickname_history_minimal.json"
+    ));
+    assert!(minimal[0].voided_at.is_none());
+
+    let full: PlayerData = data(include_str!("../tests/fixtures/player_data_full.json"));
+    let nickname = &full.nickname_history[0];
+    assert!(nickname.active.is_some() && nickname.voided_at.is_some());
+    let infraction = &full.infractions[0];
+    assert!(
+        infraction.executor.is_some()
+            && infraction.length.is_some()
+            && infraction.created_at.is_some()
+    );
+    let tracker = &full.tracker;
+    assert!(
+        tracker.server.is_some()
+            && tracker.map.is_some()
+            && tracker.proxy.is_some()
+            && tracker.last_login.is_some()
+    );
+    let ip = &full.ip_history.unwrap()[0];
+    assert!(ip.logout_at.is_some() && ip.connection_proxy.is_some());
+    let minimal: PlayerData = data(include_str!("../tests/fixtures/player_data_minimal.json"));
+    assert!(minimal.nickname_history.is_empty() && minimal.infractions.is_empty());
+    assert!(minimal.tracker.server.is_none() && minimal.tracker.last_login.is_none());
+    assert!(minimal.ip_history.is_none());
+
+    let full: Vec<StaffTracker> = data(include_str!("../tests/fixtures/staff_tracker_full.json"));
+    assert!(full.iter().all(|staff| staff.online.is_some()));
+    let minimal: Vec<StaffTracker> =
+        data(include_str!("../tests/fixtures/staff_tracker_minimal.json"));
+    assert!(minimal[0].online.is_none());
+
+    let full: PunishmentData = data(include_str!("../tests/fixtures/punishment_data_full.json"));
+    assert!(full.executor.is_some() && full.length.is_some());
+    let minimal: PunishmentData = data(include_str!(
+        "../tests/fixtures/punishment_data_minimal.json"
+    ));
+    assert!(minimal.executor.is_none() && minimal.length.is_none());
+
+    let full: KeyData = data(include_str!("../tests/fixtures/key_full.json"));
+    assert!(full.created_at.is_some() && full.expires_at.is_some());
+    assert!(full.owner_cactiveconnections_id.is_some() && !full.endpoints.is_empty());
+    let minimal: KeyData = data(include_str!("../tests/fixtures/key_minimal.json"));
+    assert!(minimal.created_at.is_none() && minimal.expires_at.is_none());
+    assert!(minimal.owner_cactiveconnections_id.is_none() && minimal.endpoints.is_empty());
+}
+
+#[test]
+fn nickname_history_result_test() {
+    let history: Vec<NicknameHistory> = serde_json::from_str(
+        r#"[
+            {"uuid":"a","nickname":"n","active":false,"created_at":"2020-01-01T00:00:00Z","voided_at":"2020-06-01T00:00:00Z"},
+            {"uuid":"b","nickname":"n","active":true,"created_at":"2020-09-01T00:00:00Z"},
+            {"uuid":"c","nickname":"n","active":false,"created_at":"2021-01-01T00:00:00Z","voided_at":"2021-03-01T00:00:00Z"}
+        ]"#,
+    )
+    .unwrap();
+
+    let result = NicknameHistoryResult::from(history);
+    let uuids = |entries: &[NicknameHistory]| {
+        entries
+            .iter()
+            .map(|entry| entry.uuid.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(uuids(&result.active), ["b"]);
+    assert_eq!(uuids(&result.historical), ["a", "c"]);
+    assert_eq!(
+        NicknameHistoryResult::from(Vec::new()),
+        NicknameHistoryResult::default()
+    );
+}
+
+#[test]
+fn ip_history_or_empty_test() {
+    let withheld: PlayerData = serde_json::from_str(r#"{"uuid":"a","tracker":{}}"#).unwrap();
+    assert!(withheld.ip_history.is_none());
+    assert!(withheld.ip_history_or_empty().is_empty());
+
+    let empty: PlayerData =
+        serde_json::from_str(r#"{"uuid":"a","tracker":{},"ip_history":[]}"#).unwrap();
+    assert_eq!(empty.ip_history.as_ref().map(Vec::len), Some(0));
+    assert!(empty.ip_history_or_empty().is_empty());
+
+    let player: PlayerData = serde_json::from_str(
+        r#"{"uuid":"a","tracker":{},"ip_history":[{"ip":"203.0.113.42","login_at":"2022-08-05T12:34:56Z"}]}"#,
+    )
+    .unwrap();
+    assert_eq!(player.ip_history_or_empty()[0].ip, "203.0.113.42");
+}
+
+#[test]
+fn total_punishment_duration_test() {
+    let player = |infractions: &str| -> PlayerData {
+        serde_json::from_str(&format!(
+            r#"{{"uuid":"a","tracker":{{}},"infractions":{infractions}}}"#
+        ))
+        .unwrap()
+    };
+
+    assert_eq!(player("[]").total_punishment_duration(), Duration::ZERO);
+    assert_eq!(
+        player(r#"[{"id":"A","punishment_type":"BAN","reason":"r"}]"#).total_punishment_duration(),
+        Duration::ZERO
+    );
+    assert_eq!(
+        player(
+            r#"[
+                {"id":"A","punishment_type":"MUTE","reason":"r","length":3600},
+                {"id":"B","punishment_type":"BAN","reason":"r"},
+                {"id":"C","punishment_type":"BAN","reason":"r","length":86400}
+            ]"#
+        )
+        .total_punishment_duration(),
+        Duration::from_secs(90000)
+    );
+    assert_eq!(
+        player(
+            r#"[
+                {"id":"A","punishment_type":"BAN","reason":"r","length":4294967295},
+                {"id":"B","punishment_type":"BAN","reason":"r","length":4294967295}
+            ]"#
+        )
+        .total_punishment_duration(),
+        Duration::from_secs(2 * u64::from(u32::MAX))
+    );
+}
+
+#[test]
+fn infractions_sorted_test() {
+    let player: APIData<PlayerData> = serde_json::from_str(include_str!(
+        "../tests/fixtures/player_data_infractions.json"
+    ))
+    .unwrap();
+    let player = player.data.unwrap();
+
+    let ids = player
+        .infractions_sorted()
+        .into_iter()
+        .map(|infraction| infraction.id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        ids,
+        ["C256D602", "E6F7A8B9", "D4E5F6A7", "A1B2C3D4", "B2C3D4E5"]
+    );
+}
+
+#[test]
+fn current_nickname_test() {
+    let player: PlayerData = serde_json::from_str(
+        r#"{"uuid":"a","tracker":{},"nickname_history":[
+            {"nickname":"old","active":true,"created_at":"2021-01-01T00:00:00Z"},
+            {"nickname":"voided","active":false,"created_at":"2023-01-01T00:00:00Z"},
+            {"nickname":"new","active":true,"created_at":"2022-01-01T00:00:00Z"},
+            {"nickname":"unknown","created_at":"2024-01-01T00:00:00Z"}
+        ]}"#,
+    )
+    .unwrap();
+    assert_eq!(player.current_nickname(), Some("new"));
+
+    let player: PlayerData = serde_json::from_str(
+        r#"{"uuid":"a","tracker":{},"nickname_history":[
+            {"nickname":"voided","active":false,"created_at":"2023-01-01T00:00:00Z"}
+        ]}"#,
+    )
+    .unwrap();
+    assert_eq!(player.current_nickname(), None);
+}
+
+#[test]
+fn parse_timestamp_millis_test() {
+    assert_eq!(
+        parse_timestamp_millis("2022-08-05T12:34:56.789Z").unwrap(),
+        1659702896789
+    );
+    assert_eq!(
+        parse_timestamp_millis("2022-08-05 14:34:56.7891+02:00").unwrap(),
+        1659702896789
+    );
+    assert_eq!(parse_timestamp_millis("1970-01-01T00:00:00Z").unwrap(), 0);
+    assert_eq!(
+        parse_timestamp_millis("1969-12-31T23:59:59.9995Z").unwrap(),
+        -1
+    );
+    assert_eq!(
+        parse_timestamp_millis("yesterday").unwrap_err().r#type,
+        "invalid-timestamp"
+    );
+
+    let tracker: PlayerDataTracker =
+        serde_json::from_str(r#"{"last_login":"2022-08-05T12:34:56Z"}"#).unwrap();
+    assert_eq!(tracker.last_login_millis(), Some(1659702896000));
+    let tracker: PlayerDataTracker = serde_json::from_str("{}").unwrap();
+    assert_eq!(tracker.last_login_millis(), None);
+}
+
+#[test]
+fn staff_rank_test() {
+    let staff: Vec<StaffTracker> = serde_json::from_str(
+        r#"[
+            {"uuid":"a","rank":"ADMIN","online":true},
+            {"uuid":"b","rank":"MOD"},
+            {"uuid":"c","rank":"Moderator"},
+            {"uuid":"d","rank":"HELPER"},
+            {"uuid":"e","rank":"BUILD_TEAM"},
+            {"uuid":"f","rank":"build_team"}
+        ]"#,
+    )
+    .unwrap();
+    let uuids = |staff: Vec<&StaffTracker>| {
+        staff
+            .into_iter()
+            .map(|member| member.uuid.clone())
+            .collect::<Vec<_>>()
+    };
+
+    assert!(staff[0].has_rank(StaffRank::Admin));
+    assert!(!staff[0].has_rank(StaffRank::Moderator));
+    assert_eq!(staff[2].staff_rank(), StaffRank::Moderator);
+    assert_eq!(uuids(with_rank(&staff, StaffRank::Moderator)), ["b", "c"]);
+    assert_eq!(
+        uuids(with_rank(&staff, StaffRank::Owner)),
+        Vec::<&str>::new()
+    );
+    assert_eq!(
+        uuids(with_rank(&staff, StaffRank::Other("BUILD_TEAM".to_owned()))),
+        ["e"]
+    );
+    assert!(!staff[1].has_rank(StaffRank::Other("MOD".to_owned())));
+}
+
+#[cfg(feature = "csv")]
+#[test]
+fn csv_record_test() {
+    let data = |json: &str| {
+        serde_json::from_str::<APIData<PlayerData>>(json)
+            .unwrap()
+            .data
+            .unwrap()
+    };
+
+    let full = data(include_str!("../tests/fixtures/player_data_full.json"));
+    let record = full.to_csv_record();
+    assert_eq!(record.len(), PlayerData::csv_header().len());
+    assert_eq!(
+        record,
+        [
+            "eea2d4fda8b8413b9439f06faaf7e109",
+            "",
+            "1",
+            "2022-08-05T12:34:56.789Z"
+        ]
+    );
+
+    let minimal = data(include_str!("../tests/fixtures/player_data_minimal.json"));
+    assert_eq!(minimal.to_csv_record()[1..], ["", "0", ""]);
+}
+
+#[test]
+fn player_data_diff_test() {
+    let data = |json: &str| {
+        serde_json::from_str::<APIData<PlayerData>>(json)
+            .unwrap()
+            .data
+            .unwrap()
+    };
+    let before = data(include_str!("../tests/fixtures/player_data_before.json"));
+    let after = data(include_str!("../tests/fixtures/player_data_after.json"));
+
+    let diff = before.diff(&after);
+    let ids = |infractions: &[&PlayerDataInfractions]| {
+        infractions
+            .iter()
+            .map(|infraction| infraction.id.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ids(&diff.added_infractions), ["D9A41B37"]);
+    assert_eq!(ids(&diff.removed_infractions), ["A1B2C3D4"]);
+    let nicknames = |entries: &[&PlayerDataNicknameHistory]| {
+        entries
+            .iter()
+            .map(|entry| entry.nickname.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(nicknames(&diff.added_nicknames), ["quiet_fox"]);
+    assert_eq!(nicknames(&diff.voided_nicknames), ["angry_and_free"]);
+    let ips = diff
+        .added_ip_history
+        .iter()
+        .map(|entry| entry.ip.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(ips, ["198.51.100.7"]);
+    assert!(!diff.is_empty());
+    assert!(format!("{diff:?}").contains("D9A41B37"));
+
+    assert!(after.diff(&after).is_empty());
+    let withheld = data(include_str!("../tests/fixtures/player_data_minimal.json"));
+    assert!(withheld.diff(&after).added_ip_history.is_empty());
+    assert_eq!(ids(&withheld.diff(&after).added_infractions).len(), 2);
+}
+
+#[cfg(feature = "capture-unknown")]
+#[test]
+fn unknown_fields_test() {
+    let tracker = |json: &str| serde_json::from_str::<StaffTracker>(json).unwrap();
+
+    let first = tracker(r#"{"uuid":"a","rank":"MOD","drift_test_x":1,"drift_test_y":2}"#);
+    assert_eq!(first.extra.len(), 2);
+    let fields = ["drift_test_x".to_owned(), "drift_test_z".to_owned()];
+    assert_eq!(
+        newly_unknown_fields("StaffTracker", fields.iter()),
+        ["drift_test_z"]
+    );
+    assert_eq!(
+        newly_unknown_fields("PlayerData", fields.iter()),
+        ["drift_test_x", "drift_test_z"]
+    );
+    assert!(newly_unknown_fields("StaffTracker", fields.iter()).is_empty());
+}
+
+#[test]
+fn nickname_history_ref_voided_at_test() {
+    fn voided_at(json: &str) -> Option<Cow<'_, str>> {
+        serde_json::from_str::<NicknameHistoryRef>(json)
+            .unwrap()
+            .voided_at
+    }
+    let entry = |voided_at: &str| {
+        format!(
+            r#"{{"uuid":"a","nickname":"n","active":true,"created_at":"2022-01-01T00:00:00Z"{voided_at}}}"#
+        )
+    };
+
+    assert_eq!(voided_at(&entry("")), None);
+    assert_eq!(voided_at(&entry(r#","voided_at":null"#)), None);
+    assert_eq!(voided_at(&entry(r#","voided_at":"""#)), None);
+    let json = entry(r#","voided_at":"2022-02-01T00:00:00Z""#);
+    assert!(matches!(
+        voided_at(&json),
+        Some(Cow::Borrowed("2022-02-01T00:00:00Z"))
+    ));
+}